@@ -0,0 +1,304 @@
+use fixed::traits::FixedSigned;
+use crate::utility;
+
+/// Update the gain of an automatic gain control loop from the level of a block.
+///
+/// The gain moves a fraction `1/τ` of the way towards the gain which would bring the block to the target level.
+/// The attack time constant is used when the gain is reduced, and the decay time constant when the gain is increased.
+///
+/// ## Arguments
+/// * `gain`     - The current gain.
+/// * `level`    - The measured level of the block, before the gain.
+/// * `target`   - The target level after the gain.
+/// * `attack`   - The attack time constant in blocks. Must be at least one.
+/// * `decay`    - The decay time constant in blocks. Must be at least one.
+/// * `max_gain` - The upper limit of the gain, reached on silent input.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::agc;
+///
+/// let one = FixedI32::<U20>::from_num(1);
+/// let gain = agc::update_gain( one, FixedI32::<U20>::from_num(0.25), one, one*2, one*4, one*100 );
+/// assert_eq!{ gain, 1.75 };
+/// ```
+pub fn update_gain<T>( gain:T, level:T, target:T, attack:T, decay:T, max_gain:T ) -> T
+    where T: FixedSigned
+{
+    let desired = if level.saturating_mul(max_gain) <= target { max_gain } else { target/level };
+    let time_constant = if desired < gain { attack } else { decay };
+    return gain+(desired-gain)/time_constant;
+}
+
+/// Convert a linear amplitude gain to decibels, `20log10(gain)`.
+/// Non-positive gains return the minimum value of the type.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::agc;
+///
+/// let gain_db = agc::gain_db( FixedI32::<U20>::from_num(10) );
+/// assert_eq!{ gain_db.to_num::<f32>(), 19.999943 };
+/// ```
+pub fn gain_db<T>( gain:T ) -> T
+    where T: FixedSigned
+{
+    if gain <= T::from_num(0)
+    {
+        return T::MIN;
+    }
+    return utility::log10_fixed(gain).saturating_mul( T::from_num(20) );
+}
+
+/// This macro implements an automatic gain control (AGC) for real arrays.
+///
+/// The level of each block is measured as its RMS value, accumulated in wide integers so that loud blocks do not overflow.
+/// The gain is updated towards the gain which brings the block to the target level, with separate attack and decay time constants.
+/// The updated gain is then applied to the block through `scale()`.
+/// The gain is kept across blocks.
+///
+/// ## Arguments
+/// * `name` - The name of the AGC type. E.g. Agc.
+/// * `arr`  - The name of the real array type to process. E.g. Arr32.
+/// * `T`    - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate an AGC with unity gain.
+///
+/// ## Arguments
+/// * `target`   - The target RMS level of the output.
+/// * `attack`   - The attack time constant in blocks, used when the gain decreases. Must be at least one.
+/// * `decay`    - The decay time constant in blocks, used when the gain increases. Must be at least one.
+/// * `max_gain` - The upper limit of the gain, reached on silent input.
+///
+/// # `::process`
+/// Apply the AGC to a block.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_agc_real!( Agc, Arr4, FixedI32<U20> );
+///
+/// let mut agc = Agc::new( FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(4), FixedI32::<U20>::from_num(100) );
+/// let x = Arr4::new_from_f32_array( [0.5, -0.5, 0.5, -0.5] );
+/// let y = agc.process( &x );
+/// assert_eq!{ y.as_array_f32(), [0.625, -0.625, 0.625, -0.625] };
+/// for _i in 0..50 {
+///     agc.process( &x );
+/// }
+/// // The gain has settled to bring the RMS level from 0.5 to 1.
+/// assert!{ 1.999 < agc.gain() && agc.gain() < 2.001 };
+/// assert!{ 6.01 < agc.gain_db() && agc.gain_db() < 6.03 };
+/// ```
+///
+/// # `::gain`, `::gain_db` and `::set_gain`
+/// Get the current linear gain, the current gain in dB, or override the gain.
+#[macro_export]
+macro_rules! declare_agc_real{
+    ( $name:ident, $arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Automatic gain control for real arrays.
+        pub struct $name{
+            gain: $T,
+            target: $T,
+            attack: $T,
+            decay: $T,
+            max_gain: $T,
+        }
+
+        impl $name {
+            /// Generate an AGC with unity gain.
+            #[allow(dead_code)]
+            fn new( target:$T, attack:$T, decay:$T, max_gain:$T ) -> Self {
+                $name {
+                    gain: <$T>::from_num(1),
+                    target,
+                    attack,
+                    decay,
+                    max_gain,
+                }
+            }
+            /// Returns the current linear gain.
+            #[allow(dead_code)]
+            fn gain( &self ) -> $T {
+                return self.gain;
+            }
+            /// Returns the current gain in dB.
+            #[allow(dead_code)]
+            fn gain_db( &self ) -> $T {
+                return integer_array::agc::gain_db( self.gain );
+            }
+            /// Override the current linear gain.
+            #[allow(dead_code)]
+            fn set_gain( &mut self, gain:$T ) {
+                self.gain = gain;
+            }
+            /// Update the gain from the RMS level of the block, and apply it.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$arr ) -> $arr {
+                let level = input.rms();
+                self.gain = integer_array::agc::update_gain( self.gain, level, self.target, self.attack, self.decay, self.max_gain );
+                return input.scale( self.gain );
+            }
+        }
+    }
+}
+
+/// This macro implements an automatic gain control (AGC) for complex arrays.
+///
+/// The level of each block is measured as its mean magnitude, accumulated in wide integers so that loud blocks do not overflow.
+/// The gain is updated towards the gain which brings the block to the target level, with separate attack and decay time constants.
+/// The updated gain is then applied to the block through `scale()`.
+/// The gain is kept across blocks.
+///
+/// ## Arguments
+/// * `name`        - The name of the AGC type. E.g. Agc.
+/// * `complex_arr` - The name of the complex array type to process. E.g. CArr32.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`, `::gain`, `::gain_db` and `::set_gain`
+/// See the real AGC. The target is the mean magnitude of the output.
+///
+/// # `::process`
+/// Apply the AGC to a block.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr4, Arr4, 4, FixedI32<U20> );
+/// ia::declare_agc_complex!( Agc, CArr4, FixedI32<U20> );
+///
+/// let mut agc = Agc::new( FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(2), FixedI32::<U20>::from_num(8), FixedI32::<U20>::from_num(100) );
+/// let x = CArr4::new_from_f32( 3.0, 4.0 );
+/// for _i in 0..20 {
+///     agc.process( &x );
+/// }
+/// let y = agc.process( &x );
+/// assert!{ 0.999 < y.mag().mean() && y.mag().mean() < 1.001 };
+/// assert!{ agc.gain_db() < -13.97 && -13.99 < agc.gain_db() };
+/// ```
+#[macro_export]
+macro_rules! declare_agc_complex{
+    ( $name:ident, $complex_arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Automatic gain control for complex arrays.
+        pub struct $name{
+            gain: $T,
+            target: $T,
+            attack: $T,
+            decay: $T,
+            max_gain: $T,
+        }
+
+        impl $name {
+            /// Generate an AGC with unity gain.
+            #[allow(dead_code)]
+            fn new( target:$T, attack:$T, decay:$T, max_gain:$T ) -> Self {
+                $name {
+                    gain: <$T>::from_num(1),
+                    target,
+                    attack,
+                    decay,
+                    max_gain,
+                }
+            }
+            /// Returns the current linear gain.
+            #[allow(dead_code)]
+            fn gain( &self ) -> $T {
+                return self.gain;
+            }
+            /// Returns the current gain in dB.
+            #[allow(dead_code)]
+            fn gain_db( &self ) -> $T {
+                return integer_array::agc::gain_db( self.gain );
+            }
+            /// Override the current linear gain.
+            #[allow(dead_code)]
+            fn set_gain( &mut self, gain:$T ) {
+                self.gain = gain;
+            }
+            /// Update the gain from the mean magnitude of the block, and apply it.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$complex_arr ) -> $complex_arr {
+                use integer_array::matrix::Element;
+                let mut sum = 0i128;
+                for item in input.data.iter() {
+                    sum += integer_array::utility::wide_bits( Element::abs( *item ) );
+                }
+                let level = integer_array::utility::from_wide_bits::<$T>( sum/(input.data.len() as i128) );
+                self.gain = integer_array::agc::update_gain( self.gain, level, self.target, self.attack, self.decay, self.max_gain );
+                return input.scale( self.gain );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+
+    #[test]
+    fn silence_is_limited_by_max_gain() {
+        integer_array::declare_array_real!( Arr8, 8, FixedI32<U20> );
+        integer_array::declare_agc_real!( Agc, Arr8, FixedI32<U20> );
+
+        let mut agc = Agc::new( FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(10) );
+        agc.process( &Arr8::new_from_i32(0) );
+        assert_eq!{ agc.gain(), 10 };
+
+        // A loud block is attacked at once with a time constant of one block.
+        agc.process( &Arr8::new_from_i32(4) );
+        assert!{ 0.249 < agc.gain() && agc.gain() < 0.251 };
+    }
+
+    #[test]
+    fn extreme_levels_and_gains_do_not_overflow() {
+        type T = fixed::types::I12F20;
+        let one = T::from_num(1);
+        // The level times the maximum gain is beyond the range of the type.
+        let gain = super::update_gain( one, T::from_num(30), one, one, one, T::from_num(100) );
+        assert!{ 0.033 < gain && gain < 0.034 };
+
+        integer_array::declare_array_real!( Arr8, 8, T );
+        integer_array::declare_agc_real!( Agc, Arr8, T );
+        let mut agc = Agc::new( one, one, one, T::from_num(10) );
+        agc.set_gain( T::from_num(0) );
+        assert_eq!{ agc.gain_db(), T::MIN };
+    }
+
+    #[test]
+    fn loud_blocks_are_attenuated() {
+        type T = FixedI32<U20>;
+        let one = T::from_num(1);
+        // The squares of the samples are far beyond the range of the type.
+        integer_array::declare_array_real!( Arr8, 8, T );
+        integer_array::declare_agc_real!( Agc, Arr8, T );
+        let mut agc = Agc::new( one, one, one, T::from_num(10) );
+        let y = agc.process( &Arr8::new_from_f32( 100.0 ) );
+        assert!{ 0.0099 < agc.gain() && agc.gain() < 0.0101 };
+        assert!{ 0.99 < y[0] && y[0] < 1.01 };
+
+        integer_array::declare_array_complex!( CArr8, CArr8Real, 8, T );
+        integer_array::declare_agc_complex!( CAgc, CArr8, T );
+        let mut agc = CAgc::new( one, one, one, T::from_num(10) );
+        let y = agc.process( &CArr8::new_from_f32( 1000.0, -1000.0 ) );
+        assert!{ 0.000706 < agc.gain() && agc.gain() < 0.000709 };
+        assert!{ 0.706 < y[0].re && y[0].re < 0.708 };
+    }
+}
@@ -0,0 +1,168 @@
+//! Ring buffers for the state of streaming blocks.
+
+/// This macro implements a circular buffer, or delay line, of the N most recent items.
+///
+/// The items are either `$T` or `Complex<$T>`, and the buffer pairs with a real or complex array of length N.
+/// Pushing an item overwrites the oldest one, without moving the other items.
+/// Items are indexed by their age, where index 0 is the newest item and index N-1 the oldest.
+/// The buffer starts out filled with zeros.
+///
+/// ## Arguments
+/// * `name` - The name of the buffer type. E.g. Delay8.
+/// * `arr`  - The name of a real or complex array type of length N, for snapshots. E.g. Arr8 or CArr8.
+/// * `N`    - The number of items in the buffer.
+/// * `item` - The item type, `$T` or `num::complex::Complex<$T>`. Must be the item type of `arr`.
+///
+/// # `::new`, `::len` and `::reset`
+/// Generate a buffer of zeros, get its length, or clear it to zeros.
+///
+/// # `::push`
+/// Push an item, and return the item that is pushed out. A buffer of length N thus delays by N samples.
+///
+/// # `::push_block`
+/// Push a block of items in order, e.g. the `data` of an array of any length.
+///
+/// # Indexing, `::newest` and `::oldest`
+/// Get an item by its age, or the newest and oldest items.
+///
+/// # `::snapshot`
+/// Copy the buffer into an array in chronological order, with the oldest item first and the newest last.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_circular_buffer!( Delay4, Arr4, 4, FixedI32<U20> );
+///
+/// let mut delay = Delay4::new();
+/// let x = Arr4::new_from_f32_array( [1.0, 2.0, 3.0, 4.0] );
+/// delay.push_block( &x.data );
+/// assert_eq!{ delay.push( FixedI32::<U20>::from_num(5) ), 1 };
+/// assert_eq!{ delay[0], 5 };
+/// assert_eq!{ delay[3], 2 };
+/// assert_eq!{ delay.snapshot().as_array_i32(), [2, 3, 4, 5] };
+/// ```
+///
+/// With complex items.
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+///
+/// ia::declare_array_complex!( CArr2, Arr2, 2, FixedI32<U20> );
+/// ia::declare_circular_buffer!( ComplexDelay2, CArr2, 2, Complex<FixedI32<U20>> );
+///
+/// let mut delay = ComplexDelay2::new();
+/// delay.push( Complex::new( FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(-1) ) );
+/// assert_eq!{ delay.newest().im, -1 };
+/// assert_eq!{ delay.oldest().im, 0 };
+/// ```
+#[macro_export]
+macro_rules! declare_circular_buffer{
+    ( $name:ident, $arr:ident, $N:expr, $item:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Circular buffer of the most recent items.
+        pub struct $name{
+            data: [$item; $N],
+            // The position of the oldest item, which the next push overwrites.
+            position: usize,
+        }
+
+        impl $name {
+            /// Generate a buffer of zeros.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                $name {
+                    data: [<$item>::default(); $N],
+                    position: 0,
+                }
+            }
+            /// The number of items in the buffer.
+            #[allow(dead_code)]
+            fn len( &self ) -> usize {
+                return $N;
+            }
+            /// Clear the buffer to zeros.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new();
+            }
+            /// Push an item, and return the oldest item, which is pushed out.
+            #[allow(dead_code)]
+            fn push( &mut self, item:$item ) -> $item {
+                let oldest = self.data[self.position];
+                self.data[self.position] = item;
+                self.position = (self.position+1)%$N;
+                return oldest;
+            }
+            /// Push a block of items in order.
+            #[allow(dead_code)]
+            fn push_block( &mut self, input:&[$item] ) {
+                for item in input.iter() {
+                    self.push( *item );
+                }
+            }
+            /// The newest item.
+            #[allow(dead_code)]
+            fn newest( &self ) -> $item {
+                return self[0];
+            }
+            /// The oldest item.
+            #[allow(dead_code)]
+            fn oldest( &self ) -> $item {
+                return self.data[self.position];
+            }
+            /// Copy the buffer into an array, with the oldest item first.
+            #[allow(dead_code)]
+            fn snapshot( &self ) -> $arr {
+                let mut r_array = $arr { data: [<$item>::default(); $N] };
+                for n in 0..$N {
+                    r_array.data[n] = self.data[(self.position+n)%$N];
+                }
+                return r_array;
+            }
+        }
+
+        impl core::ops::Index<usize> for $name {
+            type Output = $item;
+            /// Returns the item of the given age, where 0 is the newest item.
+            #[inline]
+            fn index( &self, age:usize ) -> &$item {
+                assert!{ age < $N };
+                return &self.data[(self.position+$N-1-age)%$N];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+
+    #[test]
+    fn buffer_keeps_the_most_recent_items() {
+        integer_array::declare_array_real!( Arr5, 5, FixedI32<U20> );
+        integer_array::declare_circular_buffer!( Delay5, Arr5, 5, FixedI32<U20> );
+
+        let mut delay = Delay5::new();
+        for n in 0..13usize {
+            let out = delay.push( FixedI32::<U20>::from_num(n) );
+            // The input, delayed by five samples.
+            assert_eq!{ out, n.saturating_sub(5) as i32 };
+            for age in 0..5 {
+                assert_eq!{ delay[age], n.saturating_sub(age) as i32 };
+            }
+        }
+        assert_eq!{ delay.snapshot().data, [8, 9, 10, 11, 12] };
+        delay.reset();
+        assert_eq!{ delay.snapshot().data, [0, 0, 0, 0, 0] };
+    }
+}
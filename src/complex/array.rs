@@ -41,6 +41,21 @@
 /// assert_eq!{ x.imag(), Arr4::new_from_i32(2) };
 /// ```
 /// 
+/// # `::scale`
+/// Scale every item in the array with a real scalar value.
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex as C;
+/// 
+/// ia::declare_array_complex!( CArr2, Arr2, 2, FixedI32<U20> );
+/// let x = CArr2::new_from_f32( 1.0, -2.0 );
+/// let y = x.scale( FixedI32::<U20>::from_num(1.5) );
+/// assert_eq!{ y.as_array_f32(), [ C{re:1.5, im:-3.0}, C{re:1.5, im:-3.0} ] };
+/// ```
+/// 
 /// # `::mag`
 /// Get the item-wise magnitude of the complex array.
 /// 
@@ -52,11 +67,11 @@
 /// ia::declare_array_complex!( CArr4, Arr4, 4, FixedI32<U20> );
 /// let x = CArr4::new_from_f32( 1.0, 2.0 );
 /// let y = x.mag();
-/// assert_eq!{ y.as_array_f32(), [1.75, 1.75, 1.75, 1.75] };
+/// assert_eq!{ y.as_array_f32(), [2.2360678, 2.2360678, 2.2360678, 2.2360678] };
 /// ```
 /// 
 /// # `::arg`
-/// Get the item-wise argumetn of the complex array.
+/// Get the item-wise argumetn of the complex array, in the -π<θ=<π range.
 /// 
 /// ```rust
 /// use integer_array as ia;
@@ -66,7 +81,85 @@
 /// ia::declare_array_complex!( CArr4, Arr4, 4, FixedI32<U20> );
 /// let x = CArr4::new_from_f32( 1.0, 2.0 );
 /// let y = x.arg();
-/// assert_eq!{ y.as_array_f32(), [1.1032009, 1.1032009, 1.1032009, 1.1032009] };
+/// assert_eq!{ y.as_array_f32(), [1.1070986, 1.1070986, 1.1070986, 1.1070986] };
+/// ```
+/// 
+/// # `::mean`, `::var` and `::rms`
+/// Get the complex mean, the variance `E[|z-μ|²]` and the root mean square `sqrt(E[|z|²])` of the array.
+/// The sums are accumulated in wide integers, so the squares do not overflow the type.
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex as C;
+/// 
+/// ia::declare_array_complex!( CArr2, Arr2, 2, FixedI32<U20> );
+/// let mut x = CArr2::new_from_f32( 3.0, 4.0 );
+/// x.data[1] = C::new( FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(0) );
+/// assert_eq!{ x.mean(), C::new( FixedI32::<U20>::from_num(2), FixedI32::<U20>::from_num(2) ) };
+/// assert_eq!{ x.var(), 5 };
+/// assert_eq!{ x.rms().to_num::<f32>(), 3.6055508 };
+/// ```
+/// 
+/// # `::interpolate_linear`, `::interpolate_cubic` and `::interpolate_lagrange`
+/// Look up the array at a fractional index, by linear, cubic Hermite, or Lagrange interpolation of a given order.
+/// Indices beyond the ends take the end items. See the `interpolation` module.
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex as C;
+/// 
+/// ia::declare_array_complex!( CArr4, Arr4, 4, FixedI32<U20> );
+/// let mut x = CArr4::new_from_i32( 0, 0 );
+/// for (n, item) in x.data.iter_mut().enumerate() {
+///     *item = C::new( FixedI32::<U20>::from_num(n), FixedI32::<U20>::from_num(-2*n as i32) );
+/// }
+/// let index = FixedI32::<U20>::from_num(1.25);
+/// assert_eq!{ x.interpolate_linear( index ), C::new( FixedI32::<U20>::from_num(1.25), FixedI32::<U20>::from_num(-2.5) ) };
+/// assert_eq!{ x.interpolate_cubic( index ), x.interpolate_lagrange( index, 3 ) };
+/// ```
+/// 
+/// # `::map_bits` and `::map_symbols`
+/// Generate an array of Gray-coded BPSK, QPSK, 8-PSK, 16-QAM or 64-QAM points, see `modulation::Constellation`.
+/// The bits are given one per item, MSB first. The points are optionally scaled to unit average power.
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::modulation::Constellation;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex as C;
+/// 
+/// ia::declare_array_complex!( CArr2, Arr2, 2, FixedI32<U20> );
+/// let x = CArr2::map_bits( &[0, 1, 1, 1], Constellation::Qpsk, false );
+/// assert_eq!{ x.as_array_f32(), [ C{re:1.0, im:-1.0}, C{re:-1.0, im:-1.0} ] };
+/// 
+/// let x = CArr2::map_symbols( &[0b0000, 0b1010], Constellation::Qam16, true );
+/// assert_eq!{ x.as_array_f32(), [ C{re:0.94868374, im:0.94868374}, C{re:-0.94868374, im:-0.94868374} ] };
+/// ```
+/// 
+/// # `::demap_bits` and `::demap_llrs`
+/// Demap the array to hard bits, or to max-log log-likelihood ratios scaled by the noise variance, see `modulation::demap_llrs`.
+/// A positive LLR favours a zero bit.
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::modulation::Constellation;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_complex!( CArr2, Arr2, 2, FixedI32<U20> );
+/// let x = CArr2::map_bits( &[0, 1, 1, 1], Constellation::Qpsk, true );
+/// let mut bits = [0u8; 4];
+/// x.demap_bits( Constellation::Qpsk, true, &mut bits );
+/// assert_eq!{ bits, [0, 1, 1, 1] };
+/// 
+/// let mut llrs = [FixedI32::<U20>::from_num(0); 4];
+/// x.demap_llrs( Constellation::Qpsk, true, FixedI32::<U20>::from_num(1), &mut llrs );
+/// assert_eq!{ llrs.map( |llr| llr.to_num::<f32>() ), [1.9999981, -1.9999981, -1.9999981, -1.9999981] };
 /// ```
 #[macro_export]
 macro_rules! declare_array_complex{
@@ -75,12 +168,19 @@ macro_rules! declare_array_complex{
         // Declare the real array counterpart.
         integer_array::declare_array_real!($real_name, $N, $T);
 
-        #[derive(Copy, Clone, Default, Debug, PartialEq)]
+        #[derive(Copy, Clone, Debug, PartialEq)]
         /// Real numeric array of type int32.
         pub struct $name{
             pub data: [num::complex::Complex<$T>; $N],
         }
 
+        // Implemented by hand, as arrays longer than 32 items do not implement Default.
+        impl Default for $name {
+            fn default() -> Self {
+                return $name::new_from_i32( 0, 0 );
+            }
+        }
+
         impl $name {
             /// Generate an array of a value.
             #[allow(dead_code)]
@@ -177,6 +277,20 @@ macro_rules! declare_array_complex{
             }
         }
 
+        impl core::convert::From<[num::complex::Complex<$T>; $N]> for $name {
+            /// Wrap a primitive array.
+            fn from( data:[num::complex::Complex<$T>; $N] ) -> Self {
+                return $name { data };
+            }
+        }
+
+        impl core::convert::AsRef<[num::complex::Complex<$T>; $N]> for $name {
+            /// Borrow the items as a primitive array.
+            fn as_ref( &self ) -> &[num::complex::Complex<$T>; $N] {
+                return &self.data;
+            }
+        }
+
         impl $name {
             /// Return the real component of the complex array
             #[allow(dead_code)]
@@ -198,6 +312,17 @@ macro_rules! declare_array_complex{
                 return r_array;
             }
 
+            /// Scales the array by a real scalar value.
+            #[allow(dead_code)]
+            fn scale( &self, value:$T ) -> Self {
+                let mut r_array = self.clone();
+                for item in r_array.data.iter_mut() {
+                    item.re *= value;
+                    item.im *= value;
+                }
+                return r_array;
+            }
+
             /// Return the real component of the complex array
             #[allow(dead_code)]
             fn mag( &self ) -> $real_name {
@@ -215,10 +340,110 @@ macro_rules! declare_array_complex{
             fn arg( &self ) -> $real_name {
                 let mut r_array = $real_name::new_from_i32(0);
                 for n in 0..$N {
-                    r_array[n] = integer_array::utility::atan2_precise_fixed( self[n].im, self[n].re );
+                    r_array[n] = integer_array::utility::atan2_fixed( self[n].im, self[n].re );
                 }
                 return r_array;
             }
+
+            /// Return the mean of the array, accumulated in wide integers.
+            #[allow(dead_code)]
+            fn mean( &self ) -> num::complex::Complex<$T> {
+                let mut re:i128 = 0;
+                let mut im:i128 = 0;
+                for item in self.data.iter() {
+                    re += item.re.to_bits() as i128;
+                    im += item.im.to_bits() as i128;
+                }
+                return num::complex::Complex::new( integer_array::utility::from_wide_bits::<$T>( re/($N as i128) ), integer_array::utility::from_wide_bits::<$T>( im/($N as i128) ) );
+            }
+            /// The mean of |z|², with twice the fractional bits of the type.
+            #[allow(dead_code)]
+            fn mean_square_bits( &self ) -> i128 {
+                let mut sum:i128 = 0;
+                for item in self.data.iter() {
+                    let re = item.re.to_bits() as i128;
+                    let im = item.im.to_bits() as i128;
+                    sum += re*re+im*im;
+                }
+                return sum/($N as i128);
+            }
+            /// Return the variance of the array, `E[|z-μ|²] = E[|z|²]-|μ|²`, accumulated in wide integers.
+            #[allow(dead_code)]
+            fn var( &self ) -> $T {
+                let mean = self.mean();
+                let re = mean.re.to_bits() as i128;
+                let im = mean.im.to_bits() as i128;
+                let variance = (self.mean_square_bits()-re*re-im*im).max(0);
+                return integer_array::utility::from_wide_bits::<$T>( variance >> <$T>::FRAC_NBITS );
+            }
+            /// Return the root mean square of the array, `sqrt(E[|z|²])`, accumulated in wide integers.
+            #[allow(dead_code)]
+            fn rms( &self ) -> $T {
+                return integer_array::utility::from_wide_bits::<$T>( (self.mean_square_bits() as u128).isqrt() as i128 );
+            }
+            /// Return the linearly interpolated value at a fractional index, see `interpolation::linear`.
+            #[allow(dead_code)]
+            fn interpolate_linear( &self, index:$T ) -> num::complex::Complex<$T> {
+                return integer_array::interpolation::linear( &self.data, index );
+            }
+            /// Return the cubic Hermite interpolated value at a fractional index, see `interpolation::cubic_hermite`.
+            #[allow(dead_code)]
+            fn interpolate_cubic( &self, index:$T ) -> num::complex::Complex<$T> {
+                return integer_array::interpolation::cubic_hermite( &self.data, index );
+            }
+            /// Return the Lagrange interpolated value of an order at a fractional index, see `interpolation::lagrange`.
+            #[allow(dead_code)]
+            fn interpolate_lagrange( &self, index:$T, order:usize ) -> num::complex::Complex<$T> {
+                return integer_array::interpolation::lagrange( &self.data, index, order );
+            }
+        }
+
+        impl $name {
+            /// Generate an array of Gray-coded constellation points from a buffer of symbols.
+            /// Items without a symbol are zero.
+            #[allow(dead_code)]
+            fn map_symbols( symbols:&[u8], constellation:integer_array::modulation::Constellation, unit_power:bool ) -> Self {
+                let mut r_array = $name::new_from_i32( 0, 0 );
+                for (item, symbol) in r_array.data.iter_mut().zip( symbols.iter() ) {
+                    *item = integer_array::modulation::map_symbol( *symbol, constellation, unit_power );
+                }
+                return r_array;
+            }
+
+            /// Generate an array of Gray-coded constellation points from a buffer with one bit per item, MSB first.
+            /// Items without a complete symbol are padded with zero bits, and items without any bits are zero.
+            #[allow(dead_code)]
+            fn map_bits( bits:&[u8], constellation:integer_array::modulation::Constellation, unit_power:bool ) -> Self {
+                let mut r_array = $name::new_from_i32( 0, 0 );
+                let bits_per_symbol = constellation.bits_per_symbol();
+                let symbols = bits.len().div_ceil( bits_per_symbol );
+                for (n, item) in r_array.data.iter_mut().take( symbols ).enumerate() {
+                    let symbol = integer_array::modulation::pack_bits( bits, bits_per_symbol, n );
+                    *item = integer_array::modulation::map_symbol( symbol, constellation, unit_power );
+                }
+                return r_array;
+            }
+
+            /// Hard decision of every item to the nearest Gray-coded symbol, written to a buffer with one bit per item, MSB first.
+            /// Bits which do not fit in the buffer are dropped.
+            #[allow(dead_code)]
+            fn demap_bits( &self, constellation:integer_array::modulation::Constellation, unit_power:bool, bits:&mut [u8] ) {
+                let bits_per_symbol = constellation.bits_per_symbol();
+                for (n, item) in self.data.iter().enumerate() {
+                    let symbol = integer_array::modulation::demap_symbol( *item, constellation, unit_power );
+                    integer_array::modulation::unpack_bits( symbol, bits_per_symbol, n, bits );
+                }
+            }
+
+            /// Max-log LLRs of the bits of every item, written to a buffer with one LLR per bit, MSB first.
+            /// LLRs which do not fit in the buffer are dropped.
+            #[allow(dead_code)]
+            fn demap_llrs( &self, constellation:integer_array::modulation::Constellation, unit_power:bool, noise_variance:$T, llrs:&mut [$T] ) {
+                let bits_per_symbol = constellation.bits_per_symbol();
+                for (item, chunk) in self.data.iter().zip( llrs.chunks_mut( bits_per_symbol ) ) {
+                    integer_array::modulation::demap_llrs( *item, constellation, unit_power, noise_variance, chunk );
+                }
+            }
         }
 
         impl $name {
@@ -0,0 +1,253 @@
+use fixed::traits::FixedSigned;
+use num::complex::Complex;
+
+/// The discriminator of an FM demodulator.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Discriminator {
+    /// The argument of the conjugate product of successive samples, `arg(z[n]·z*[n-1])`.
+    /// Exact for phase steps within ±π.
+    Polar,
+    /// The quadrature-differentiating discriminator, `(I·ΔQ-Q·ΔI)/(I^2+Q^2)`.
+    /// Avoids the arctangent, and gives `sin(Δφ)` for a phase step Δφ.
+    Quadrature,
+}
+
+/// The quadrature-differentiating discriminator of a sample and its predecessor.
+///
+/// Returns zero if the sample is zero.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::demodulation;
+///
+/// let previous = Complex::new( FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(0) );
+/// let item     = Complex::new( FixedI32::<U20>::from_num(0.8), FixedI32::<U20>::from_num(0.6) );
+/// assert_eq!{ demodulation::quadrature_discriminator( item, previous ).to_num::<f32>(), 0.6000004 };
+/// ```
+pub fn quadrature_discriminator<T>( item:Complex<T>, previous:Complex<T> ) -> T
+    where T: FixedSigned
+{
+    let power = item.re*item.re+item.im*item.im;
+    if power == T::from_num(0)
+    {
+        return T::from_num(0);
+    }
+    return ( item.re*(item.im-previous.im)-item.im*(item.re-previous.re) )/power;
+}
+
+/// This macro implements an FM demodulator for complex arrays.
+///
+/// The output is the instantaneous frequency in radians per sample.
+/// Divide by `2π·deviation/sample_rate` to normalize a deviation to one.
+///
+/// The last sample of each block is kept, so the first sample of the next block is demodulated against it.
+///
+/// ## Arguments
+/// * `name`        - The name of the demodulator type. E.g. FmDemodulator.
+/// * `complex_arr` - The name of the complex input array type. E.g. CArr32.
+/// * `real_arr`    - The name of the real counterpart of the complex array type. E.g. Arr32.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate a demodulator with the polar or quadrature-differentiating discriminator, see `demodulation::Discriminator`.
+///
+/// # `::demodulate`
+/// Demodulate a block of samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::demodulation::Discriminator;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex as C;
+///
+/// ia::declare_array_complex!( CArr4, Arr4, 4, FixedI32<U20> );
+/// ia::declare_fm_demodulator!( FmDemodulator, CArr4, Arr4, FixedI32<U20> );
+///
+/// // A tone at a quarter of the sample rate, π/2 radians per sample.
+/// let one  = FixedI32::<U20>::from_num(1);
+/// let zero = FixedI32::<U20>::from_num(0);
+/// let mut x = CArr4::new_from_i32( 0, 0 );
+/// x.data = [ C::new( one, zero ), C::new( zero, one ), C::new( -one, zero ), C::new( zero, -one ) ];
+///
+/// let mut fm = FmDemodulator::new( Discriminator::Polar );
+/// fm.demodulate( &x );
+/// let y = fm.demodulate( &x );
+/// assert!{ y.data.iter().all( |item| 1.5703 < *item && *item < 1.5713 ) };
+/// ```
+///
+/// # `::reset`
+/// Clear the kept sample.
+#[macro_export]
+macro_rules! declare_fm_demodulator{
+    ( $name:ident, $complex_arr:ident, $real_arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// FM demodulator.
+        pub struct $name{
+            discriminator: integer_array::demodulation::Discriminator,
+            previous: num::complex::Complex<$T>,
+        }
+
+        impl $name {
+            /// Generate a demodulator with a cleared state.
+            #[allow(dead_code)]
+            fn new( discriminator:integer_array::demodulation::Discriminator ) -> Self {
+                $name {
+                    discriminator,
+                    previous: num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ),
+                }
+            }
+            /// Clear the kept sample.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new( self.discriminator );
+            }
+            /// Demodulate a block of samples to the instantaneous frequency in radians per sample.
+            #[allow(dead_code)]
+            fn demodulate( &mut self, input:&$complex_arr ) -> $real_arr {
+                let mut r_array = $real_arr::new_from_i32( 0 );
+                match self.discriminator {
+                    integer_array::demodulation::Discriminator::Polar => {
+                        let mut product = $complex_arr::new_from_i32( 0, 0 );
+                        for n in 0..input.data.len() {
+                            // z[n]·z*[n-1]
+                            product.data[n] = num::complex::Complex::new( input[n].re*self.previous.re+input[n].im*self.previous.im,
+                                                                          input[n].im*self.previous.re-input[n].re*self.previous.im );
+                            self.previous = input[n];
+                        }
+                        r_array = product.arg();
+                    },
+                    integer_array::demodulation::Discriminator::Quadrature => {
+                        for n in 0..input.data.len() {
+                            r_array[n] = integer_array::demodulation::quadrature_discriminator( input[n], self.previous );
+                            self.previous = input[n];
+                        }
+                    },
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+/// This macro implements an AM envelope demodulator for complex arrays.
+///
+/// The envelope is the magnitude of the samples, `mag()`.
+/// The carrier is optionally removed by subtracting a running average of the envelope, `dc += α(|z|-dc)`.
+/// The running average is kept across blocks.
+///
+/// ## Arguments
+/// * `name`        - The name of the demodulator type. E.g. AmDemodulator.
+/// * `complex_arr` - The name of the complex input array type. E.g. CArr32.
+/// * `real_arr`    - The name of the real counterpart of the complex array type. E.g. Arr32.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate a demodulator with the smoothing factor α of the carrier level, in the 0=<α<=1 range.
+/// An α of zero keeps the carrier, and outputs the plain envelope.
+///
+/// # `::demodulate`
+/// Demodulate a block of samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr4, Arr4, 4, FixedI32<U20> );
+/// ia::declare_am_demodulator!( AmDemodulator, CArr4, Arr4, FixedI32<U20> );
+///
+/// let x = CArr4::new_from_f32( 0.6, -0.8 );
+/// let mut am = AmDemodulator::new( FixedI32::<U20>::from_num(0) );
+/// assert_eq!{ am.demodulate( &x ).as_array_f32(), [0.99999905, 0.99999905, 0.99999905, 0.99999905] };
+///
+/// // The output decays as the carrier level settles.
+/// let mut am = AmDemodulator::new( FixedI32::<U20>::from_num(0.5) );
+/// assert_eq!{ am.demodulate( &x ).as_array_f32(), [0.5, 0.25, 0.125, 0.0625] };
+/// ```
+///
+/// # `::carrier` and `::reset`
+/// Get or clear the running average of the envelope.
+#[macro_export]
+macro_rules! declare_am_demodulator{
+    ( $name:ident, $complex_arr:ident, $real_arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// AM envelope demodulator.
+        pub struct $name{
+            alpha: $T,
+            carrier: $T,
+        }
+
+        impl $name {
+            /// Generate a demodulator with a cleared carrier level.
+            #[allow(dead_code)]
+            fn new( alpha:$T ) -> Self {
+                $name {
+                    alpha,
+                    carrier: <$T>::from_num(0),
+                }
+            }
+            /// Returns the running average of the envelope.
+            #[allow(dead_code)]
+            fn carrier( &self ) -> $T {
+                return self.carrier;
+            }
+            /// Clear the carrier level.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.carrier = <$T>::from_num(0);
+            }
+            /// Demodulate a block of samples to the envelope, less the carrier level.
+            #[allow(dead_code)]
+            fn demodulate( &mut self, input:&$complex_arr ) -> $real_arr {
+                let mut r_array = input.mag();
+                for n in 0..r_array.data.len() {
+                    self.carrier += self.alpha*(r_array[n]-self.carrier);
+                    r_array[n] -= self.carrier;
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+    use super::Discriminator;
+
+    #[test]
+    fn fm_state_is_kept_across_blocks() {
+        integer_array::declare_array_complex!( CArr8, Arr8, 8, FixedI32<U20> );
+        integer_array::declare_fm_demodulator!( FmDemodulator, CArr8, Arr8, FixedI32<U20> );
+
+        let omega = -0.3f64;
+        for discriminator in [Discriminator::Polar, Discriminator::Quadrature].iter() {
+            let mut fm = FmDemodulator::new( *discriminator );
+            let expected = if *discriminator == Discriminator::Polar { omega } else { omega.sin() };
+            for block in 0..3 {
+                let mut x = CArr8::new_from_i32( 0, 0 );
+                for (n, item) in x.data.iter_mut().enumerate() {
+                    let phase = omega*(8*block+n) as f64;
+                    *item = num::complex::Complex::new( FixedI32::<U20>::from_num(0.5*phase.cos()), FixedI32::<U20>::from_num(0.5*phase.sin()) );
+                }
+                let y = fm.demodulate( &x );
+                // The first sample of the first block has no predecessor.
+                let first = if block == 0 { 1 } else { 0 };
+                for item in y.data[first..].iter() {
+                    assert!{ (item.to_num::<f64>()-expected).abs() < 0.001 };
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,639 @@
+use fixed::traits::FixedSigned;
+use num::complex::Complex;
+use crate::utility;
+
+/// Complex product of two fixed-point complex numbers.
+pub fn complex_mul<T>( a:Complex<T>, b:Complex<T> ) -> Complex<T>
+    where T: FixedSigned
+{
+    return Complex::new( a.re*b.re-a.im*b.im, a.re*b.im+a.im*b.re );
+}
+
+/// Complex conjugate of a fixed-point complex number.
+pub fn complex_conj<T>( a:Complex<T> ) -> Complex<T>
+    where T: FixedSigned
+{
+    return Complex::new( a.re, -a.im );
+}
+
+/// Fill a table with the twiddle factors `W_L^k = e^(-j2πk/L)` for `k` in `0..L/2`, where `L` is twice the table length.
+///
+/// The table serves every power-of-two FFT of length up to `L`.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::fft;
+///
+/// let mut table = [Complex::new( FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(0) ); 4];
+/// fft::twiddles( &mut table );
+/// assert!{ table[2].re.abs() < 0.00001 };
+/// assert_eq!{ table[2].im, -1 };
+/// ```
+pub fn twiddles<T>( table:&mut [Complex<T>] )
+    where T: FixedSigned
+{
+    let length = 2*table.len();
+    for (k, item) in table.iter_mut().enumerate() {
+        *item = unit_phasor( k, length );
+    }
+}
+
+/// Fill a table with the twiddle factors `W_N^k = e^(-j2πk/N)` for `k` in `0..N`, where `N` is the table length.
+///
+/// The table serves the mixed-radix FFT of length N, see `fft::mixed_radix`.
+pub fn full_twiddles<T>( table:&mut [Complex<T>] )
+    where T: FixedSigned
+{
+    let length = table.len();
+    for (k, item) in table.iter_mut().enumerate() {
+        *item = unit_phasor( k, length );
+    }
+}
+
+/// The phasor `e^(-j2πk/N)`. The fraction k/N is formed before the conversion to fixed point, so that N may exceed the range of T.
+fn unit_phasor<T>( k:usize, length:usize ) -> Complex<T>
+    where T: FixedSigned
+{
+    let phase = -T::from_num(fixed::consts::TAU)*T::from_num( (k%length) as f64/length as f64 );
+    return Complex::new( utility::cos_precise_fixed(phase), utility::sin_precise_fixed(phase) );
+}
+
+/// In-place iterative radix-2 decimation-in-time FFT.
+///
+/// The forward transform is unscaled, `X[k] = Σx[n]e^(-j2πkn/N)`, and the output grows with up to a factor N.
+/// The inverse transform is scaled by 1/N, halving the data in every stage to avoid overflow.
+///
+/// ## Arguments
+/// * `data`     - The data to transform. The length must be a power of two.
+/// * `twiddles` - A twiddle table from `fft::twiddles`, with at least half as many items as `data`.
+/// * `inverse`  - Calculate the inverse transform.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::fft;
+///
+/// let zero = FixedI32::<U20>::from_num(0);
+/// let mut table = [Complex::new( zero, zero ); 2];
+/// fft::twiddles( &mut table );
+///
+/// let mut data = [Complex::new( zero, zero ); 4];
+/// data[1].re = FixedI32::<U20>::from_num(1);
+/// fft::fft( &mut data, &table, false );
+/// assert!{ data[1].re.abs() < 0.00001 };
+/// assert_eq!{ data[1].im, -1 };
+/// ```
+pub fn fft<T>( data:&mut [Complex<T>], twiddles:&[Complex<T>], inverse:bool )
+    where T: FixedSigned
+{
+    let length = data.len();
+    if length < 2
+    {
+        return;
+    }
+
+    // Bit-reversed reordering.
+    let bits = length.trailing_zeros();
+    for n in 0..length {
+        let reversed = n.reverse_bits() >> (usize::BITS-bits);
+        if n < reversed
+        {
+            data.swap( n, reversed );
+        }
+    }
+
+    let half = T::from_num(0.5f32);
+    let mut size = 2;
+    while size <= length
+    {
+        let stride = 2*twiddles.len()/size;
+        for start in (0..length).step_by(size) {
+            for j in 0..size/2 {
+                let mut w = twiddles[j*stride];
+                if inverse
+                {
+                    w.im = -w.im;
+                }
+                let a = data[start+j];
+                let b = complex_mul( data[start+j+size/2], w );
+                if inverse
+                {
+                    data[start+j]        = Complex::new( (a.re+b.re)*half, (a.im+b.im)*half );
+                    data[start+j+size/2] = Complex::new( (a.re-b.re)*half, (a.im-b.im)*half );
+                }
+                else
+                {
+                    data[start+j]        = Complex::new( a.re+b.re, a.im+b.im );
+                    data[start+j+size/2] = Complex::new( a.re-b.re, a.im-b.im );
+                }
+            }
+        }
+        size *= 2;
+    }
+}
+
+/// Returns true if the length only has the prime factors 2, 3 and 5, and is supported by `fft::mixed_radix`.
+pub const fn is_mixed_radix( length:usize ) -> bool {
+    if length == 0
+    {
+        return false;
+    }
+    let mut rest = length;
+    while rest.is_multiple_of(2) { rest /= 2; }
+    while rest.is_multiple_of(3) { rest /= 3; }
+    while rest.is_multiple_of(5) { rest /= 5; }
+    return rest == 1;
+}
+
+/// The length of the mixed-radix twiddle table of an FFT of the given length. Zero if Bluestein's algorithm is used.
+pub const fn mixed_radix_length( length:usize ) -> usize {
+    if is_mixed_radix( length )
+    {
+        return length;
+    }
+    return 0;
+}
+
+/// The power-of-two convolution length `M>=2N-1` of Bluestein's algorithm for an FFT of the given length. Zero if the mixed-radix FFT is used.
+pub const fn bluestein_length( length:usize ) -> usize {
+    if is_mixed_radix( length ) || length == 0
+    {
+        return 0;
+    }
+    return (2*length-1).next_power_of_two();
+}
+
+/// The length of the chirp table of Bluestein's algorithm for an FFT of the given length. Zero if the mixed-radix FFT is used.
+pub const fn bluestein_chirp_length( length:usize ) -> usize {
+    if is_mixed_radix( length )
+    {
+        return 0;
+    }
+    return length;
+}
+
+/// Mixed-radix decimation-in-time FFT for lengths with the prime factors 2, 3 and 5.
+///
+/// Stages of radix 4 are used where possible, followed by radix 2, 3 and 5.
+/// The forward transform is unscaled, and the inverse transform is scaled by 1/N, dividing by the radix in every stage.
+///
+/// ## Arguments
+/// * `input`    - The data to transform.
+/// * `output`   - The transformed data, of the same length as the input.
+/// * `twiddles` - A twiddle table from `fft::full_twiddles` or `twiddle_table`, of the same length as the input.
+/// * `inverse`  - Calculate the inverse transform.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::fft;
+///
+/// let zero = FixedI32::<U20>::from_num(0);
+/// let mut table = [Complex::new( zero, zero ); 3];
+/// fft::full_twiddles( &mut table );
+///
+/// let input = [Complex::new( FixedI32::<U20>::from_num(1), zero ); 3];
+/// let mut output = [Complex::new( zero, zero ); 3];
+/// fft::mixed_radix( &input, &mut output, &table, false );
+/// assert_eq!{ output[0].re, 3 };
+/// assert!{ output[1].re.abs() < 0.00001 && output[2].re.abs() < 0.00001 };
+/// ```
+pub fn mixed_radix<T>( input:&[Complex<T>], output:&mut [Complex<T>], twiddles:&[Complex<T>], inverse:bool )
+    where T: FixedSigned
+{
+    mixed_radix_stage( input, 0, 1, output, twiddles, inverse );
+}
+
+/// One recursive stage of the mixed-radix FFT, transforming `input[offset+n·stride]` into `output`.
+fn mixed_radix_stage<T>( input:&[Complex<T>], offset:usize, stride:usize, output:&mut [Complex<T>], twiddles:&[Complex<T>], inverse:bool )
+    where T: FixedSigned
+{
+    let length = output.len();
+    if length == 1
+    {
+        output[0] = input[offset];
+        return;
+    }
+    let radix = [4, 2, 3, 5].iter().copied().find( |radix| length.is_multiple_of(*radix) ).unwrap_or( length );
+    let m     = length/radix;
+
+    for q in 0..radix {
+        mixed_radix_stage( input, offset+q*stride, stride*radix, &mut output[q*m..(q+1)*m], twiddles, inverse );
+    }
+
+    // W_length^e from the table of length N.
+    let step = twiddles.len()/length;
+    let twiddle = |exponent:usize| -> Complex<T> {
+        let w = twiddles[(exponent%length)*step];
+        if inverse
+        {
+            return Complex::new( w.re, -w.im );
+        }
+        return w;
+    };
+    let scale = if inverse { T::from_num(1)/T::from_num(radix) } else { T::from_num(1) };
+    let zero  = Complex::new( T::from_num(0), T::from_num(0) );
+
+    for k in 0..m {
+        let mut y = [zero; 5];
+        for (q, item) in y.iter_mut().take( radix ).enumerate() {
+            let temp = complex_mul( output[q*m+k], twiddle( q*k ) );
+            *item = Complex::new( temp.re*scale, temp.im*scale );
+        }
+        for r in 0..radix {
+            let mut sum = zero;
+            for (q, item) in y.iter().take( radix ).enumerate() {
+                let temp = complex_mul( *item, twiddle( ((q*r)%radix)*m ) );
+                sum = Complex::new( sum.re+temp.re, sum.im+temp.im );
+            }
+            output[k+r*m] = sum;
+        }
+    }
+}
+
+/// Fill the tables of Bluestein's algorithm for an FFT of length N.
+///
+/// ## Arguments
+/// * `chirp`          - The chirp `c[n] = e^(-jπn^2/N)`, of length N.
+/// * `chirp_spectrum` - The unscaled FFT of the conjugate chirp, wrapped around to the convolution length M.
+/// * `twiddles`       - The twiddles of the radix-2 FFT of length M, of length M/2.
+pub fn bluestein_tables<T>( chirp:&mut [Complex<T>], chirp_spectrum:&mut [Complex<T>], twiddles:&mut [Complex<T>] )
+    where T: FixedSigned
+{
+    let length = chirp.len();
+    let zero   = Complex::new( T::from_num(0), T::from_num(0) );
+    self::twiddles( twiddles );
+    for item in chirp_spectrum.iter_mut() {
+        *item = zero;
+    }
+    for (n, item) in chirp.iter_mut().enumerate() {
+        // πn^2/N = 2π(n^2 mod 2N)/2N
+        *item = unit_phasor( (n*n)%(2*length), 2*length );
+        chirp_spectrum[n] = Complex::new( item.re, -item.im );
+        if 0 < n
+        {
+            let wrapped = chirp_spectrum.len()-n;
+            chirp_spectrum[wrapped] = chirp_spectrum[n];
+        }
+    }
+    fft( chirp_spectrum, twiddles, false );
+}
+
+/// FFT of arbitrary length N through Bluestein's algorithm, as a circular convolution of power-of-two length M.
+///
+/// The forward transform is unscaled, and the inverse transform is scaled by 1/N.
+/// The input to the convolution is scaled by 1/M, so precision is lower than for the mixed-radix FFT.
+///
+/// ## Arguments
+/// * `data`           - The data to transform in place, of length N.
+/// * `chirp`          - The chirp table from `fft::bluestein_tables`.
+/// * `chirp_spectrum` - The chirp spectrum from `fft::bluestein_tables`.
+/// * `twiddles`       - The radix-2 twiddles from `fft::bluestein_tables`.
+/// * `scratch`        - A buffer of length M.
+/// * `inverse`        - Calculate the inverse transform.
+pub fn bluestein<T>( data:&mut [Complex<T>], chirp:&[Complex<T>], chirp_spectrum:&[Complex<T>], twiddles:&[Complex<T>], scratch:&mut [Complex<T>], inverse:bool )
+    where T: FixedSigned
+{
+    let length = data.len();
+    if inverse
+    {
+        // IDFT(x) = conj(DFT(conj(x)))/N
+        let scale = T::from_num(1)/T::from_num(length);
+        for item in data.iter_mut() {
+            *item = Complex::new( item.re*scale, -item.im*scale );
+        }
+    }
+
+    // a[n] = x[n]c[n], zero-padded. The conjugate is taken to run a scaled forward FFT as an inverse FFT.
+    for (n, item) in scratch.iter_mut().enumerate() {
+        *item = if n < length { complex_conj( complex_mul( data[n], chirp[n] ) ) } else { Complex::new( T::from_num(0), T::from_num(0) ) };
+    }
+    fft( scratch, twiddles, true );
+
+    // The product of DFT(a)/M and DFT(b), conjugated again to run the inverse FFT as an unscaled forward FFT.
+    for (item, b) in scratch.iter_mut().zip( chirp_spectrum.iter() ) {
+        *item = complex_conj( complex_mul( complex_conj(*item), *b ) );
+    }
+    fft( scratch, twiddles, false );
+
+    for (n, item) in data.iter_mut().enumerate() {
+        let temp = complex_mul( complex_conj(scratch[n]), chirp[n] );
+        *item = if inverse { complex_conj(temp) } else { temp };
+    }
+}
+
+/// This macro implements an FFT of arbitrary length N for complex arrays.
+///
+/// Lengths with the prime factors 2, 3 and 5, such as 12, 48, 96 or 1536, use the mixed-radix FFT in `fft::mixed_radix`.
+/// Other lengths fall back to Bluestein's algorithm in `fft::bluestein`.
+/// The tables of both are sized at compile time, and the tables of the unused algorithm have zero length.
+/// The mixed-radix twiddles are generated at compile time by `twiddle_table`, while the Bluestein tables are calculated by `new`.
+///
+/// The forward transform is unscaled, and the inverse transform is scaled by 1/N.
+///
+/// ## Arguments
+/// * `name`        - The name of the FFT type. E.g. Fft.
+/// * `complex_arr` - The name of the complex array type, of length N. E.g. CArr12.
+/// * `N`           - The length of the complex array.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate the FFT, with its tables.
+///
+/// # `::fft` and `::ifft`
+/// Transform a complex array, or inverse transform it.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr12, Arr12, 12, FixedI32<U20> );
+/// ia::declare_fft!( Fft, CArr12, 12, FixedI32<U20> );
+///
+/// let fft = Fft::new();
+/// let mut x = CArr12::new_from_i32( 0, 0 );
+/// x.data[1].re = FixedI32::<U20>::from_num(1);
+/// let spectrum = fft.fft( &x );
+/// // A delay of one sample, e^(-j2πk/12).
+/// assert!{ 0.999 < spectrum[0].re && spectrum[3].re.abs() < 0.001 && spectrum[3].im < -0.999 };
+///
+/// let y = fft.ifft( &spectrum );
+/// assert!{ 0.999 < y[1].re && y[0].re.abs() < 0.001 };
+/// ```
+#[macro_export]
+macro_rules! declare_fft{
+    ( $name:ident, $complex_arr:ident, $N:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Mixed-radix or Bluestein FFT.
+        pub struct $name{
+            chirp: [num::complex::Complex<$T>; integer_array::fft::bluestein_chirp_length($N)],
+            chirp_spectrum: [num::complex::Complex<$T>; integer_array::fft::bluestein_length($N)],
+            bluestein_twiddles: [num::complex::Complex<$T>; integer_array::fft::bluestein_length($N)/2],
+        }
+
+        impl $name {
+            /// The mixed-radix twiddles, generated at compile time.
+            #[allow(dead_code)]
+            const TWIDDLES: [num::complex::Complex<$T>; integer_array::fft::mixed_radix_length($N)]
+                = integer_array::twiddle_table!( integer_array::fft::mixed_radix_length($N), $T );
+
+            /// Generate the FFT, with its tables.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                let zero = num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) );
+                let mut fft = $name {
+                    chirp: [zero; integer_array::fft::bluestein_chirp_length($N)],
+                    chirp_spectrum: [zero; integer_array::fft::bluestein_length($N)],
+                    bluestein_twiddles: [zero; integer_array::fft::bluestein_length($N)/2],
+                };
+                if !integer_array::fft::is_mixed_radix($N)
+                {
+                    integer_array::fft::bluestein_tables( &mut fft.chirp, &mut fft.chirp_spectrum, &mut fft.bluestein_twiddles );
+                }
+                return fft;
+            }
+            /// Transform with either algorithm.
+            #[allow(dead_code)]
+            fn transform( &self, input:&$complex_arr, inverse:bool ) -> $complex_arr {
+                let mut r_array = *input;
+                if integer_array::fft::is_mixed_radix($N)
+                {
+                    integer_array::fft::mixed_radix( &input.data, &mut r_array.data, &Self::TWIDDLES, inverse );
+                }
+                else
+                {
+                    let mut scratch = [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); integer_array::fft::bluestein_length($N)];
+                    integer_array::fft::bluestein( &mut r_array.data, &self.chirp, &self.chirp_spectrum, &self.bluestein_twiddles, &mut scratch, inverse );
+                }
+                return r_array;
+            }
+            /// Unscaled forward FFT.
+            #[allow(dead_code)]
+            fn fft( &self, input:&$complex_arr ) -> $complex_arr {
+                return self.transform( input, false );
+            }
+            /// Inverse FFT, scaled by 1/N.
+            #[allow(dead_code)]
+            fn ifft( &self, input:&$complex_arr ) -> $complex_arr {
+                return self.transform( input, true );
+            }
+        }
+    }
+}
+
+/// This macro implements a real FFT, transforming a real array of length N to the N/2+1 non-negative frequency bins.
+///
+/// The N real samples are packed as N/2 complex samples, `z[n] = x[2n]+jx[2n+1]`, and transformed with an N/2 point complex FFT.
+/// The spectra of the even and odd samples are then separated, and combined with post-processing twiddles.
+/// The inverse runs the same steps backwards.
+///
+/// The forward transform is unscaled, and the inverse is scaled by 1/N, see `fft::fft`.
+///
+/// ## Arguments
+/// * `name`        - The name of the FFT type. E.g. RealFft.
+/// * `real_arr`    - The name of the real array type, of length N. E.g. Arr32.
+/// * `complex_arr` - The name of a complex array type of length N/2+1. E.g. CArr17.
+/// * `N`           - The length of the real array. Must be a power of two, at least 2.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate the FFT, with its twiddle table.
+///
+/// # `::fft` and `::ifft`
+/// Transform a real array to its N/2+1 bins, or the bins back to a real array.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr8, 8, FixedI32<U20> );
+/// ia::declare_array_complex!( CArr5, Arr5, 5, FixedI32<U20> );
+/// ia::declare_real_fft!( RealFft, Arr8, CArr5, 8, FixedI32<U20> );
+///
+/// let rfft = RealFft::new();
+/// let x = Arr8::new_from_f32_array( [1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, -1.0] );
+/// let spectrum = rfft.fft( &x );
+/// assert!{ spectrum[0].re.abs() < 0.001 && spectrum[1].re.abs() < 0.001 };
+/// assert!{ 3.999 < spectrum[2].re && spectrum[2].re < 4.001 && -4.001 < spectrum[2].im && spectrum[2].im < -3.999 };
+///
+/// let y = rfft.ifft( &spectrum );
+/// assert!{ y.data.iter().zip( x.data.iter() ).all( |(a, b)| (*a-*b).abs() < 0.001 ) };
+/// ```
+#[macro_export]
+macro_rules! declare_real_fft{
+    ( $name:ident, $real_arr:ident, $complex_arr:ident, $N:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Real FFT through a half-length complex FFT.
+        pub struct $name{
+            twiddles: [num::complex::Complex<$T>; $N/2],
+        }
+
+        impl $name {
+            /// Generate the FFT, with its twiddle table.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                let mut twiddles = [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $N/2];
+                integer_array::fft::twiddles( &mut twiddles );
+                $name {
+                    twiddles,
+                }
+            }
+            /// The post-processing twiddle `W_N^k` for `k` in `0..=N/2`.
+            #[allow(dead_code)]
+            fn twiddle( &self, k:usize ) -> num::complex::Complex<$T> {
+                if k < $N/2
+                {
+                    return self.twiddles[k];
+                }
+                return num::complex::Complex::new( <$T>::from_num(-1), <$T>::from_num(0) );
+            }
+            /// Transform a real array to its N/2+1 non-negative frequency bins.
+            #[allow(dead_code)]
+            fn fft( &self, input:&$real_arr ) -> $complex_arr {
+                let half = <$T>::from_num(0.5f32);
+                let mut z = [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $N/2];
+                for (n, item) in z.iter_mut().enumerate() {
+                    *item = num::complex::Complex::new( input[2*n], input[2*n+1] );
+                }
+                integer_array::fft::fft( &mut z, &self.twiddles, false );
+
+                let mut r_array = $complex_arr::new_from_i32( 0, 0 );
+                for k in 0..=$N/2 {
+                    let a = z[k%($N/2)];
+                    let b = z[($N/2-k)%($N/2)];
+                    // Spectra of the even and odd samples.
+                    let even = num::complex::Complex::new( (a.re+b.re)*half, (a.im-b.im)*half );
+                    let odd  = num::complex::Complex::new( (a.im+b.im)*half, (b.re-a.re)*half );
+                    let odd  = integer_array::fft::complex_mul( odd, self.twiddle(k) );
+                    r_array.data[k] = num::complex::Complex::new( even.re+odd.re, even.im+odd.im );
+                }
+                return r_array;
+            }
+            /// Transform N/2+1 non-negative frequency bins back to a real array.
+            #[allow(dead_code)]
+            fn ifft( &self, input:&$complex_arr ) -> $real_arr {
+                let half = <$T>::from_num(0.5f32);
+                let mut z = [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $N/2];
+                for (k, item) in z.iter_mut().enumerate() {
+                    let a = input[k];
+                    let b = input[$N/2-k];
+                    let even = num::complex::Complex::new( (a.re+b.re)*half, (a.im-b.im)*half );
+                    let mut w = self.twiddle(k);
+                    w.im = -w.im;
+                    let odd = integer_array::fft::complex_mul( num::complex::Complex::new( (a.re-b.re)*half, (a.im+b.im)*half ), w );
+                    // z = even + j·odd
+                    *item = num::complex::Complex::new( even.re-odd.im, even.im+odd.re );
+                }
+                integer_array::fft::fft( &mut z, &self.twiddles, true );
+
+                let mut r_array = $real_arr::new_from_i32( 0 );
+                for (n, item) in z.iter().enumerate() {
+                    r_array[2*n]   = item.re;
+                    r_array[2*n+1] = item.im;
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+
+    #[test]
+    fn real_fft_matches_dft() {
+        integer_array::declare_array_real!( Arr16, 16, FixedI32<U20> );
+        integer_array::declare_array_complex!( CArr9, Arr9, 9, FixedI32<U20> );
+        integer_array::declare_real_fft!( RealFft, Arr16, CArr9, 16, FixedI32<U20> );
+
+        let samples = [0.3, -0.7, 0.1, 0.9, -0.2, 0.5, -0.8, 0.05, 0.6, -0.1, 0.25, -0.45, 0.7, 0.2, -0.3, 0.4];
+        let x = Arr16::new_from_f32_array( samples );
+        let rfft = RealFft::new();
+        let spectrum = rfft.fft( &x );
+
+        for k in 0..9 {
+            let mut re = 0f64;
+            let mut im = 0f64;
+            for (n, sample) in samples.iter().enumerate() {
+                let phase = -2.0*core::f64::consts::PI*(k*n) as f64/16.0;
+                re += *sample as f64*phase.cos();
+                im += *sample as f64*phase.sin();
+            }
+            assert!{ (spectrum[k].re.to_num::<f64>()-re).abs() < 0.001 };
+            assert!{ (spectrum[k].im.to_num::<f64>()-im).abs() < 0.001 };
+        }
+
+        let y = rfft.ifft( &spectrum );
+        for (a, b) in y.data.iter().zip( samples.iter() ) {
+            assert!{ (a.to_num::<f64>()-*b as f64).abs() < 0.001 };
+        }
+    }
+
+    /// Check a complex FFT against a floating-point DFT, and the inverse against the input.
+    macro_rules! check_fft{
+        ( $N:expr, $tolerance:expr ) => {
+            {
+                integer_array::declare_array_complex!( CArr, Arr, $N, FixedI32<U20> );
+                integer_array::declare_fft!( Fft, CArr, $N, FixedI32<U20> );
+
+                let mut x = CArr::new_from_i32( 0, 0 );
+                let mut seed = 7u32;
+                for item in x.data.iter_mut() {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    let re = ((seed >> 16)%200) as f64/200.0-0.5;
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    let im = ((seed >> 16)%200) as f64/200.0-0.5;
+                    *item = num::complex::Complex::new( FixedI32::<U20>::from_num(re), FixedI32::<U20>::from_num(im) );
+                }
+                let fft = Fft::new();
+                let spectrum = fft.fft( &x );
+                for k in 0..$N {
+                    let mut re = 0f64;
+                    let mut im = 0f64;
+                    for (n, item) in x.data.iter().enumerate() {
+                        let phase = -2.0*core::f64::consts::PI*((k*n)%$N) as f64/$N as f64;
+                        re += item.re.to_num::<f64>()*phase.cos()-item.im.to_num::<f64>()*phase.sin();
+                        im += item.re.to_num::<f64>()*phase.sin()+item.im.to_num::<f64>()*phase.cos();
+                    }
+                    assert!{ (spectrum[k].re.to_num::<f64>()-re).abs() < $tolerance };
+                    assert!{ (spectrum[k].im.to_num::<f64>()-im).abs() < $tolerance };
+                }
+                let y = fft.ifft( &spectrum );
+                for (a, b) in y.data.iter().zip( x.data.iter() ) {
+                    assert!{ (a.re-b.re).abs() < $tolerance && (a.im-b.im).abs() < $tolerance };
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_radix_matches_dft() {
+        check_fft!( 12, 0.001 );
+        check_fft!( 30, 0.001 );
+        check_fft!( 96, 0.002 );
+    }
+
+    #[test]
+    fn bluestein_matches_dft() {
+        assert_eq!{ super::bluestein_length(7), 16 };
+        check_fft!( 7, 0.005 );
+        check_fft!( 22, 0.01 );
+    }
+}
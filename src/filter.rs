@@ -0,0 +1,5 @@
+pub mod cic;
+pub mod halfband;
+pub mod hilbert;
+pub mod design;
+pub mod adaptive;
@@ -0,0 +1,518 @@
+//! Adaptive FIR filters, which update their taps each sample towards a desired signal.
+//!
+//! The filters are declared for real or complex items, and keep their taps and delay line in arrays of the same type.
+//! The output of a filter with taps `w` is `y = wᴴ·x`, where `x` holds the N most recent inputs with the newest first.
+//! The error `e = d - y` is the difference between the desired sample and the output, before the taps are updated.
+//! For real items the conjugates have no effect, and the output is the ordinary FIR convolution.
+//!
+//! Updates smaller than the resolution of the type are lost, so the step size bounds the smallest error the filters converge on.
+
+/// This macro declares the struct and the methods shared by the adaptive filters, which implement `push` themselves.
+///
+/// The struct holds the taps, the delay line, the output and error of the latest sample, the parameters given to `new`,
+/// and any further state, which is initialized from the parameters by `new_from_taps`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! declare_adaptive_filter{
+    ( $name:ident, $arr:ident, $N:expr, $item:ty, $doc:expr,
+      ( $( $parameter:ident ),* ),
+      ( $( $state:ident : $state_type:ty = $initial_state:expr ),* ) ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        #[doc = $doc]
+        pub struct $name{
+            taps: $arr,
+            delay_line: $arr,
+            $( $parameter: <$item as integer_array::matrix::Element>::Real, )*
+            $( $state: $state_type, )*
+            output: $item,
+            error: $item,
+        }
+
+        impl $name {
+            /// Generate a filter with zeroed taps.
+            #[allow(dead_code)]
+            fn new( $( $parameter:<$item as integer_array::matrix::Element>::Real ),* ) -> Self {
+                use integer_array::matrix::Element;
+                return Self::new_from_taps( <$arr>::from( [<$item>::zero(); $N] ), $( $parameter ),* );
+            }
+            /// Generate a filter with the given initial taps.
+            #[allow(dead_code)]
+            fn new_from_taps( taps:$arr, $( $parameter:<$item as integer_array::matrix::Element>::Real ),* ) -> Self {
+                use integer_array::matrix::Element;
+                $name {
+                    taps,
+                    delay_line: <$arr>::from( [<$item>::zero(); $N] ),
+                    $( $state: $initial_state, )*
+                    $( $parameter, )*
+                    output: <$item>::zero(),
+                    error: <$item>::zero(),
+                }
+            }
+            /// Zero the taps and clear the state of the filter.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new( $( self.$parameter ),* );
+            }
+            /// The current taps.
+            #[allow(dead_code)]
+            fn taps( &self ) -> $arr {
+                return self.taps;
+            }
+            /// The output of the latest sample.
+            #[allow(dead_code)]
+            fn output( &self ) -> $item {
+                return self.output;
+            }
+            /// The error of the latest sample.
+            #[allow(dead_code)]
+            fn error( &self ) -> $item {
+                return self.error;
+            }
+            /// Filter an input sample with the current taps, without updating them.
+            #[allow(dead_code)]
+            fn filter( &mut self, input:$item ) -> $item {
+                use integer_array::matrix::Element;
+                self.delay_line.data.copy_within( 0..$N-1, 1 );
+                self.delay_line.data[0] = input;
+                let mut sum = <$item>::zero();
+                for (tap, x) in self.taps.data.iter().zip( self.delay_line.data.iter() ) {
+                    sum = Element::add( sum, Element::mul( Element::conj( *tap ), *x ) );
+                }
+                self.output = sum;
+                return sum;
+            }
+            /// Push blocks of input and desired samples, and write the errors.
+            #[allow(dead_code)]
+            fn push_block( &mut self, input:&[$item], desired:&[$item], error:&mut [$item] ) {
+                for ((x, d), e) in input.iter().zip( desired.iter() ).zip( error.iter_mut() ) {
+                    *e = self.push( *x, *d );
+                }
+            }
+        }
+    }
+}
+
+/// This macro implements an adaptive FIR filter which is updated by the least mean squares (LMS) algorithm.
+///
+/// The taps are updated by `w ← w + μ·x·e*`, the steepest descent on the squared error.
+/// The filter converges for step sizes below `2/(N·P)`, where P is the input power.
+///
+/// ## Arguments
+/// * `name` - The name of the filter type. E.g. Lms8.
+/// * `arr`  - The name of a real or complex array type of length N, for the taps. E.g. Arr8 or CArr8.
+/// * `N`    - The number of taps.
+/// * `item` - The item type, `$T` or `num::complex::Complex<$T>`. Must be the item type of `arr`.
+///
+/// # `::new` and `::new_from_taps`
+/// Generate a filter with the step size μ, starting from zeroed or given taps.
+///
+/// # `::push`
+/// Filter an input sample, update the taps towards the desired sample, and return the error.
+///
+/// # `::push_block`
+/// Push blocks of input and desired samples, e.g. the `data` of arrays of any length, and write the errors into a slice of the same length.
+///
+/// # `::filter`
+/// Filter an input sample with the current taps, without updating them.
+///
+/// # `::taps`, `::output` and `::error`
+/// Get the current taps, and the output and error of the latest sample.
+///
+/// # `::reset`
+/// Zero the taps and clear the state of the filter.
+///
+/// ## Example
+///
+/// Identify an unknown two-tap system from its response to a pseudo-random input.
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr2, 2, FixedI32<U20> );
+/// ia::declare_lms_filter!( Lms2, Arr2, 2, FixedI32<U20> );
+///
+/// let mut filter = Lms2::new( FixedI32::<U20>::from_num(0.1) );
+/// let (mut seed, mut previous) = (1u32, FixedI32::<U20>::from_num(0));
+/// for _ in 0..2000 {
+///     seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+///     let x = FixedI32::<U20>::from_num( (seed >> 16) as f32/32768.0-1.0 );
+///     let desired = x/2-previous/4;
+///     filter.push( x, desired );
+///     previous = x;
+/// }
+/// let taps = filter.taps();
+/// assert!{ (taps[0].to_num::<f32>()-0.5).abs() < 0.001 };
+/// assert!{ (taps[1].to_num::<f32>()+0.25).abs() < 0.001 };
+/// assert!{ filter.error().abs() < 0.001 };
+/// ```
+#[macro_export]
+macro_rules! declare_lms_filter{
+    ( $name:ident, $arr:ident, $N:expr, $item:ty ) => {
+
+        integer_array::declare_adaptive_filter!( $name, $arr, $N, $item, "Adaptive FIR filter, updated by least mean squares.",
+            ( step ),
+            () );
+
+        impl $name {
+            /// Filter an input sample, update the taps, and return the error.
+            #[allow(dead_code)]
+            fn push( &mut self, input:$item, desired:$item ) -> $item {
+                use integer_array::matrix::Element;
+                let output = self.filter( input );
+                self.error = Element::sub( desired, output );
+                let error = Element::conj( self.error );
+                for (tap, x) in self.taps.data.iter_mut().zip( self.delay_line.data.iter() ) {
+                    *tap = Element::add( *tap, Element::scale( Element::mul( *x, error ), self.step ) );
+                }
+                return self.error;
+            }
+        }
+    }
+}
+
+/// This macro implements an adaptive FIR filter which is updated by the normalized least mean squares (NLMS) algorithm.
+///
+/// The taps are updated by `w ← w + μ·x·e*/(δ + xᴴ·x)`.
+/// Normalizing by the power in the delay line makes the convergence independent of the input level, and stable for `0 < μ < 2`.
+/// The regularization δ bounds the update while the input is weak.
+/// The power is accumulated in wide integers.
+///
+/// ## Arguments
+/// * `name` - The name of the filter type. E.g. Nlms8.
+/// * `arr`  - The name of a real or complex array type of length N, for the taps. E.g. Arr8 or CArr8.
+/// * `N`    - The number of taps.
+/// * `item` - The item type, `$T` or `num::complex::Complex<$T>`. Must be the item type of `arr`.
+///
+/// # `::new` and `::new_from_taps`
+/// Generate a filter with the step size μ and the regularization δ, starting from zeroed or given taps.
+///
+/// # `::push`, `::push_block`, `::filter`, `::taps`, `::output`, `::error` and `::reset`
+/// As for `declare_lms_filter`.
+///
+/// ## Example
+///
+/// Cancel the echo of a far-end signal, which reaches the microphone through a complex two-tap path.
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+///
+/// ia::declare_array_complex!( CArr2, Arr2, 2, FixedI32<U20> );
+/// ia::declare_nlms_filter!( EchoCanceller, CArr2, 2, Complex<FixedI32<U20>> );
+///
+/// let value = |re:f32, im:f32| Complex::new( FixedI32::<U20>::from_num(re), FixedI32::<U20>::from_num(im) );
+/// let path = [value( 0.5, 0.25 ), value( -0.125, 0.0 )];
+/// let mut canceller = EchoCanceller::new( FixedI32::<U20>::from_num(0.5), FixedI32::<U20>::from_num(0.01) );
+/// let (mut seed, mut previous) = (7u32, value( 0.0, 0.0 ));
+/// for _ in 0..500 {
+///     seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+///     let far_end = value( (seed >> 16 & 1) as f32-0.5, (seed >> 17 & 1) as f32-0.5 );
+///     let (direct, delayed) = (ia::fft::complex_mul( path[0], far_end ), ia::fft::complex_mul( path[1], previous ));
+///     let echo = Complex::new( direct.re+delayed.re, direct.im+delayed.im );
+///     canceller.push( far_end, echo );
+///     previous = far_end;
+/// }
+/// // The residual echo is the error.
+/// assert!{ canceller.error().re.abs() < 0.001 && canceller.error().im.abs() < 0.001 };
+/// ```
+#[macro_export]
+macro_rules! declare_nlms_filter{
+    ( $name:ident, $arr:ident, $N:expr, $item:ty ) => {
+
+        integer_array::declare_adaptive_filter!( $name, $arr, $N, $item, "Adaptive FIR filter, updated by normalized least mean squares.",
+            ( step, regularization ),
+            () );
+
+        impl $name {
+            /// Filter an input sample, update the taps, and return the error.
+            #[allow(dead_code)]
+            fn push( &mut self, input:$item, desired:$item ) -> $item {
+                use integer_array::matrix::Element;
+                type Real = <$item as Element>::Real;
+                let output = self.filter( input );
+                self.error = Element::sub( desired, output );
+
+                let mut power = self.regularization.to_bits() as i128;
+                for x in self.delay_line.data.iter() {
+                    let magnitude = Element::abs( *x ).to_bits() as i128;
+                    power += (magnitude*magnitude) >> Real::FRAC_NBITS;
+                }
+                let power = integer_array::utility::from_wide_bits::<Real>( power.max( 1 ) );
+                let step = self.step.saturating_div( power );
+
+                let error = Element::conj( self.error );
+                for (tap, x) in self.taps.data.iter_mut().zip( self.delay_line.data.iter() ) {
+                    *tap = Element::add( *tap, Element::scale( Element::mul( *x, error ), step ) );
+                }
+                return self.error;
+            }
+        }
+    }
+}
+
+/// This macro implements an adaptive FIR filter which is updated by the sign-sign LMS algorithm.
+///
+/// The taps are updated by `w ← w + μ·sgn(x)·sgn(e)*`, where the sign of a complex item is taken of its real and imaginary parts.
+/// Every tap moves by a fixed step, so the update needs no multiplications, at the cost of slower convergence and a residual jitter of μ in the taps.
+///
+/// ## Arguments
+/// * `name` - The name of the filter type. E.g. SignLms8.
+/// * `arr`  - The name of a real or complex array type of length N, for the taps. E.g. Arr8 or CArr8.
+/// * `N`    - The number of taps.
+/// * `item` - The item type, `$T` or `num::complex::Complex<$T>`. Must be the item type of `arr`.
+///
+/// # `::new` and `::new_from_taps`
+/// Generate a filter with the step size μ, starting from zeroed or given taps.
+///
+/// # `::push`, `::push_block`, `::filter`, `::taps`, `::output`, `::error` and `::reset`
+/// As for `declare_lms_filter`.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr2, 2, FixedI32<U20> );
+/// ia::declare_sign_sign_lms_filter!( SignLms2, Arr2, 2, FixedI32<U20> );
+///
+/// let mut filter = SignLms2::new( FixedI32::<U20>::from_num(0.001) );
+/// let (mut seed, mut previous) = (1u32, FixedI32::<U20>::from_num(0));
+/// for _ in 0..2000 {
+///     seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+///     let x = FixedI32::<U20>::from_num( (seed >> 16) as f32/32768.0-1.0 );
+///     filter.push( x, x/2-previous/4 );
+///     previous = x;
+/// }
+/// let taps = filter.taps();
+/// assert!{ (taps[0].to_num::<f32>()-0.5).abs() < 0.01 };
+/// assert!{ (taps[1].to_num::<f32>()+0.25).abs() < 0.01 };
+/// ```
+#[macro_export]
+macro_rules! declare_sign_sign_lms_filter{
+    ( $name:ident, $arr:ident, $N:expr, $item:ty ) => {
+
+        integer_array::declare_adaptive_filter!( $name, $arr, $N, $item, "Adaptive FIR filter, updated by sign-sign least mean squares.",
+            ( step ),
+            () );
+
+        impl $name {
+            /// Filter an input sample, update the taps, and return the error.
+            #[allow(dead_code)]
+            fn push( &mut self, input:$item, desired:$item ) -> $item {
+                use integer_array::matrix::Element;
+                let output = self.filter( input );
+                self.error = Element::sub( desired, output );
+                let error = Element::conj( Element::signum( self.error ) );
+                for (tap, x) in self.taps.data.iter_mut().zip( self.delay_line.data.iter() ) {
+                    *tap = Element::add( *tap, Element::scale( Element::mul( Element::signum( *x ), error ), self.step ) );
+                }
+                return self.error;
+            }
+        }
+    }
+}
+
+/// This macro implements an adaptive FIR filter which is updated by the recursive least squares (RLS) algorithm.
+///
+/// RLS minimizes the exponentially weighted sum of squared errors, `Σ λ^(n-k)·|e[k]|²`, and converges in a few times N samples regardless of the spread of the input eigenvalues.
+/// It keeps the N×N inverse correlation matrix `P` as a `matrix::Matrix`, and each sample costs on the order of N² multiplications, so it is meant for a small order.
+///
+/// Each sample, with `π = P·x`:
+/// * `k = π/(λ + xᴴ·π)`, the gain vector.
+/// * `w ← w + k·e*`.
+/// * `P ← (P - k·πᴴ)/λ`.
+///
+/// `P` is kept Hermitian by mirroring its lower triangle, which keeps the recursion stable in fixed point.
+/// Should rounding still take it off positive definite, it is restarted.
+/// `P` starts out as `p0·I`, the inverse of a small initial regularization. A large `p0` converges faster, but must leave headroom in the type.
+/// The forgetting factor λ, just below one, sets the memory of the filter to about `1/(1-λ)` samples.
+///
+/// ## Arguments
+/// * `name` - The name of the filter type. E.g. Rls4.
+/// * `arr`  - The name of a real or complex array type of length N, for the taps. E.g. Arr4 or CArr4.
+/// * `N`    - The number of taps.
+/// * `item` - The item type, `$T` or `num::complex::Complex<$T>`. Must be the item type of `arr`.
+///
+/// # `::new` and `::new_from_taps`
+/// Generate a filter with the forgetting factor λ and the initial diagonal `p0` of `P`, starting from zeroed or given taps.
+///
+/// # `::push`, `::push_block`, `::filter`, `::taps`, `::output`, `::error` and `::reset`
+/// As for `declare_lms_filter`. Resetting also restores `P`.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr2, 2, FixedI32<U20> );
+/// ia::declare_rls_filter!( Rls2, Arr2, 2, FixedI32<U20> );
+///
+/// let mut filter = Rls2::new( FixedI32::<U20>::from_num(0.99), FixedI32::<U20>::from_num(100) );
+/// let (mut seed, mut previous) = (1u32, FixedI32::<U20>::from_num(0));
+/// for _ in 0..50 {
+///     seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+///     let x = FixedI32::<U20>::from_num( (seed >> 16) as f32/32768.0-1.0 );
+///     filter.push( x, x/2-previous/4 );
+///     previous = x;
+/// }
+/// // Converged within 50 samples.
+/// let taps = filter.taps();
+/// assert!{ (taps[0].to_num::<f32>()-0.5).abs() < 0.001 };
+/// assert!{ (taps[1].to_num::<f32>()+0.25).abs() < 0.001 };
+/// ```
+#[macro_export]
+macro_rules! declare_rls_filter{
+    ( $name:ident, $arr:ident, $N:expr, $item:ty ) => {
+
+        integer_array::declare_adaptive_filter!( $name, $arr, $N, $item, "Adaptive FIR filter, updated by recursive least squares.",
+            ( forgetting, initial ),
+            ( inverse_correlation: integer_array::matrix::Matrix<$item, $N, $N>
+                = integer_array::matrix::Matrix::<$item, $N, $N>::identity().scale( <$item>::from_real( initial ) ) ) );
+
+        impl $name {
+            /// Filter an input sample, update the taps, and return the error.
+            #[allow(dead_code)]
+            fn push( &mut self, input:$item, desired:$item ) -> $item {
+                use integer_array::matrix::Element;
+                type Real = <$item as Element>::Real;
+                let output = self.filter( input );
+                self.error = Element::sub( desired, output );
+
+                let pi:$arr = self.inverse_correlation.mul_vector( &self.delay_line );
+                let mut denominator = <$item>::from_real( self.forgetting );
+                for (x, p) in self.delay_line.data.iter().zip( pi.data.iter() ) {
+                    denominator = Element::add( denominator, Element::mul( Element::conj( *x ), *p ) );
+                }
+                // Rounding can take P off positive definite. It is then restarted rather than diverging.
+                if Element::re( denominator ) <= 0
+                {
+                    self.inverse_correlation = integer_array::matrix::Matrix::<$item, $N, $N>::identity().scale( <$item>::from_real( self.initial ) );
+                    return self.error;
+                }
+                let inverse = Real::from_num(1).saturating_div( Element::re( denominator ) );
+                let mut gain = pi;
+                for item in gain.data.iter_mut() {
+                    *item = Element::scale( *item, inverse );
+                }
+
+                let error = Element::conj( self.error );
+                for (tap, k) in self.taps.data.iter_mut().zip( gain.data.iter() ) {
+                    *tap = Element::add( *tap, Element::mul( *k, error ) );
+                }
+                // P is updated on and below the diagonal, and mirrored, so rounding keeps it Hermitian.
+                let inverse_forgetting = Real::from_num(1).saturating_div( self.forgetting );
+                for r in 0..$N {
+                    for c in 0..=r {
+                        let update = Element::mul( gain.data[r], Element::conj( pi.data[c] ) );
+                        let item = Element::scale( Element::sub( self.inverse_correlation.data[r][c], update ), inverse_forgetting );
+                        self.inverse_correlation.data[r][c] = item;
+                        self.inverse_correlation.data[c][r] = Element::conj( item );
+                    }
+                }
+                return self.error;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use crate::matrix::Element;
+    use fixed::{types::extra::U20, FixedI32};
+    use num::complex::Complex;
+
+    type T = FixedI32<U20>;
+
+    /// Pseudo-random samples in [-1, 1).
+    fn noise( seed:&mut u32 ) -> T {
+        *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        return T::from_num( (*seed >> 16) as f32/32768.0-1.0 );
+    }
+
+    #[test]
+    fn real_filters_identify_a_system() {
+        integer_array::declare_array_real!( Arr4, 4, T );
+        integer_array::declare_array_real!( Arr64, 64, T );
+        integer_array::declare_lms_filter!( Lms4, Arr4, 4, T );
+        integer_array::declare_nlms_filter!( Nlms4, Arr4, 4, T );
+        integer_array::declare_sign_sign_lms_filter!( SignLms4, Arr4, 4, T );
+        integer_array::declare_rls_filter!( Rls4, Arr4, 4, T );
+
+        let system = Arr4::new_from_f32_array( [0.5, -0.25, 0.125, 0.0625] );
+        let mut lms = Lms4::new( T::from_num(0.05) );
+        let mut nlms = Nlms4::new( T::from_num(0.5), T::from_num(0.01) );
+        let mut sign_lms = SignLms4::new( T::from_num(0.0005) );
+        let mut rls = Rls4::new( T::from_num(0.98), T::from_num(100) );
+
+        let mut seed = 3;
+        let mut history = Arr4::new_from_f32_array( [0.0; 4] );
+        for _ in 0..100 {
+            let mut input = Arr64::new_from_f32_array( [0.0; 64] );
+            let mut desired = input;
+            for (x, d) in input.data.iter_mut().zip( desired.data.iter_mut() ) {
+                *x = noise( &mut seed );
+                history.data.copy_within( 0..3, 1 );
+                history.data[0] = *x;
+                *d = history.data.iter().zip( system.data.iter() ).map( |(x, h)| *x**h ).sum();
+            }
+            let mut error = [T::from_num(0); 64];
+            lms.push_block( &input.data, &desired.data, &mut error );
+            nlms.push_block( &input.data, &desired.data, &mut error );
+            sign_lms.push_block( &input.data, &desired.data, &mut error );
+            rls.push_block( &input.data, &desired.data, &mut error );
+        }
+        for (taps, tolerance) in [(lms.taps(), 0.001), (nlms.taps(), 0.001), (sign_lms.taps(), 0.005), (rls.taps(), 0.001)] {
+            for (tap, h) in taps.data.iter().zip( system.data.iter() ) {
+                assert!{ (*tap-*h).abs() < tolerance };
+            }
+        }
+        assert_eq!{ rls.filter( T::from_num(1) ), rls.output() };
+        rls.reset();
+        assert_eq!{ rls.taps().data, [0, 0, 0, 0] };
+    }
+
+    #[test]
+    fn complex_filters_identify_a_system() {
+        integer_array::declare_array_complex!( CArr3, Arr3, 3, T );
+        integer_array::declare_lms_filter!( Lms3, CArr3, 3, Complex<T> );
+        integer_array::declare_sign_sign_lms_filter!( SignLms3, CArr3, 3, Complex<T> );
+        integer_array::declare_rls_filter!( Rls3, CArr3, 3, Complex<T> );
+
+        let value = |re:f32, im:f32| Complex::new( T::from_num(re), T::from_num(im) );
+        // The filter output is wᴴ·x, so the taps converge on the conjugated system.
+        let system = [value( 0.5, -0.25 ), value( 0.0, 0.25 ), value( -0.125, 0.0 )];
+        let mut lms = Lms3::new( T::from_num(0.05) );
+        let mut sign_lms = SignLms3::new( T::from_num(0.0005) );
+        let mut rls = Rls3::new( T::from_num(0.98), T::from_num(100) );
+
+        let mut seed = 11;
+        let mut history = [value( 0.0, 0.0 ); 3];
+        for _ in 0..6000 {
+            let x = Complex::new( noise( &mut seed ), noise( &mut seed ) );
+            history.copy_within( 0..2, 1 );
+            history[0] = x;
+            let mut desired = value( 0.0, 0.0 );
+            for (x, h) in history.iter().zip( system.iter() ) {
+                desired = desired.add( h.conj().mul( *x ) );
+            }
+            lms.push( x, desired );
+            sign_lms.push( x, desired );
+            rls.push( x, desired );
+        }
+        for (taps, tolerance) in [(lms.taps(), 0.001), (sign_lms.taps(), 0.005), (rls.taps(), 0.001)] {
+            for (tap, h) in taps.data.iter().zip( system.iter() ) {
+                assert!{ (tap.re-h.re).abs() < tolerance && (tap.im-h.im).abs() < tolerance };
+            }
+        }
+    }
+}
@@ -0,0 +1,416 @@
+use fixed::traits::FixedSigned;
+use crate::utility;
+
+/// Calculate the DC gain of a CIC filter, `(R*D)^M`.
+///
+/// ## Arguments
+/// * `rate`  - The rate change factor R.
+/// * `order` - The number of integrator and comb stages M.
+/// * `delay` - The differential delay of the comb stages D.
+///
+/// # Example
+///
+/// ```
+/// use integer_array::filter::cic;
+/// assert_eq!{ cic::gain( 4, 3, 1 ), 64 };
+/// ```
+pub fn gain( rate:usize, order:usize, delay:usize ) -> i64 {
+    let mut temp:i64 = 1;
+    for _i in 0..order {
+        temp *= (rate*delay) as i64;
+    }
+    return temp;
+}
+
+/// Calculate the register bit growth of a CIC filter, `ceil( M*log2(R*D) )`.
+///
+/// The integrator and comb registers wrap around on overflow.
+/// The output is correct as long as the input word length plus the bit growth fits in the 64 bit registers.
+///
+/// ## Arguments
+/// * `rate`  - The rate change factor R.
+/// * `order` - The number of integrator and comb stages M.
+/// * `delay` - The differential delay of the comb stages D.
+///
+/// # Example
+///
+/// ```
+/// use integer_array::filter::cic;
+/// assert_eq!{ cic::bit_growth( 4, 3, 1 ), 6 };
+/// assert_eq!{ cic::bit_growth( 5, 4, 1 ), 10 };
+/// ```
+pub fn bit_growth( rate:usize, order:usize, delay:usize ) -> u32 {
+    let gain = gain( rate, order, delay ) as u64;
+    if gain <= 1
+    {
+        return 0;
+    }
+    return 64 - (gain-1).leading_zeros();
+}
+
+/// Magnitude response of a CIC decimator, normalized to unity DC gain.
+///
+/// `H(f) = | sin(πDf) / ( R*D*sin(πf/R) ) |^M`
+///
+/// ## Arguments
+/// * `frequency` - The frequency relative to the decimated sample rate.
+/// * `rate`      - The decimation ratio R.
+/// * `order`     - The number of integrator and comb stages M.
+/// * `delay`     - The differential delay of the comb stages D.
+pub fn magnitude<T>( frequency:T, rate:usize, order:usize, delay:usize ) -> T
+    where T: FixedSigned
+{
+    if frequency == T::from_num(0)
+    {
+        return T::from_num(1);
+    }
+    let pi = T::from_num(fixed::consts::PI);
+    let numerator   = utility::sin_precise_fixed( pi*frequency*T::from_num(delay) );
+    let denominator = utility::sin_precise_fixed( pi*frequency/T::from_num(rate) )*T::from_num(rate*delay);
+    let mut temp:T = T::from_num(1);
+    for _i in 0..order {
+        temp *= (numerator/denominator).abs();
+    }
+    return temp;
+}
+
+/// Design an FIR filter which compensates for the passband droop of a CIC decimator.
+/// The compensator runs at the decimated rate, following the CIC filter.
+///
+/// The taps are designed by frequency sampling of the inverse CIC response up to the cutoff frequency.
+/// The center tap is adjusted after quantization so that the DC gain of the filter is exactly one.
+///
+/// ## Arguments
+/// * `taps`   - The taps to be designed. Should be of odd length.
+/// * `rate`   - The decimation ratio R.
+/// * `order`  - The number of integrator and comb stages M.
+/// * `delay`  - The differential delay of the comb stages D.
+/// * `cutoff` - The passband edge relative to the decimated sample rate, in the 0<f<0.5 range.
+///
+/// # Example
+///
+/// ```
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::filter::cic;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr15, 15, FixedI32<U20> );
+/// let mut taps = Arr15::zeros();
+/// cic::compensator( &mut taps.data, 4, 3, 1, FixedI32::<U20>::from_num(0.25) );
+/// assert_eq!{ taps.sum(), 1 };
+/// assert_eq!{ taps[0], taps[14] };
+/// ```
+pub fn compensator<T>( taps:&mut [T], rate:usize, order:usize, delay:usize, cutoff:T )
+    where T: FixedSigned
+{
+    let length = taps.len() as isize;
+    let center = (length-1)/2;
+    let tau    = T::from_num(fixed::consts::TAU);
+
+    for n in 0..length {
+        let mut temp:T = T::from_num(1);
+        for k in 1..=center {
+            let frequency = T::from_num(k)/T::from_num(length);
+            if cutoff < frequency
+            {
+                break;
+            }
+            // Keep the cosine argument within one period.
+            let m = (k*(n-center)).rem_euclid(length);
+            let phase = tau*T::from_num(m)/T::from_num(length);
+            temp += utility::cos_precise_fixed(phase)*T::from_num(2)/magnitude( frequency, rate, order, delay );
+        }
+        taps[n as usize] = temp/T::from_num(length);
+    }
+
+    // Force unity DC gain after quantization.
+    let mut sum:T = T::from_num(0);
+    for tap in taps.iter() {
+        sum += *tap;
+    }
+    taps[center as usize] += T::from_num(1)-sum;
+}
+
+/// This macro implements a cascaded integrator-comb (CIC) decimator.
+///
+/// The filter consists of M integrators running at the input rate, followed by a decimation by R,
+/// and M comb stages with a differential delay D running at the output rate.
+/// The registers are 64 bit integers with wrapping arithmetic, so no intermediate saturation is needed.
+/// The output is normalized by the DC gain `(R*D)^M`.
+///
+/// The state of the filter is kept across blocks.
+///
+/// ## Arguments
+/// * `name`    - The name of the filter type. E.g. Cic.
+/// * `in_arr`  - The name of the input array type. E.g. Arr16.
+/// * `out_arr` - The name of the output array type. E.g. Arr4. Must be R times shorter than the input array.
+/// * `R`       - The decimation ratio.
+/// * `M`       - The order of the filter, the number of integrator and comb stages.
+/// * `D`       - The differential delay of the comb stages. Usually 1 or 2.
+/// * `T`       - The fixed type of the elements.
+///
+/// The input word length plus the bit growth `ceil( M*log2(R*D) )` must not exceed 64 bits.
+///
+/// # `::decimate`
+/// Filter and decimate a block of samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr16, 16, FixedI32<U20> );
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_cic_decimator!( Cic, Arr16, Arr4, 4, 3, 1, FixedI32<U20> );
+///
+/// let mut cic = Cic::new();
+/// let x = Arr16::new_from_f32( 0.5 );
+/// let y = cic.decimate( &x );
+/// assert_eq!{ y.as_array_f32(), [0.15625, 0.46875, 0.5, 0.5] };
+/// let y = cic.decimate( &x );
+/// assert_eq!{ y.as_array_f32(), [0.5, 0.5, 0.5, 0.5] };
+/// ```
+///
+/// # `::gain` and `::bit_growth`
+/// The DC gain of the filter and the register bit growth.
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr16, 16, FixedI32<U20> );
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_cic_decimator!( Cic, Arr16, Arr4, 4, 3, 2, FixedI32<U20> );
+///
+/// let cic = Cic::new();
+/// assert_eq!{ cic.gain(), 512 };
+/// assert_eq!{ cic.bit_growth(), 9 };
+/// ```
+///
+/// # `::reset`
+/// Clear the state of the filter.
+#[macro_export]
+macro_rules! declare_cic_decimator{
+    ( $name:ident, $in_arr:ident, $out_arr:ident, $R:expr, $M:expr, $D:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// CIC decimator.
+        pub struct $name{
+            integrators: [i64; $M],
+            combs: [[i64; $D]; $M],
+            comb_index: usize,
+        }
+
+        impl $name {
+            /// Generate a filter with cleared state.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                $name {
+                    integrators: [0; $M],
+                    combs: [[0; $D]; $M],
+                    comb_index: 0,
+                }
+            }
+            /// Clear the state of the filter.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new();
+            }
+            /// Returns the DC gain of the filter.
+            #[allow(dead_code)]
+            fn gain( &self ) -> i64 {
+                return integer_array::filter::cic::gain( $R, $M, $D );
+            }
+            /// Returns the bit growth of the filter registers.
+            #[allow(dead_code)]
+            fn bit_growth( &self ) -> u32 {
+                return integer_array::filter::cic::bit_growth( $R, $M, $D );
+            }
+            /// Filter and decimate a block of samples.
+            #[allow(dead_code)]
+            fn decimate( &mut self, input: &$in_arr ) -> $out_arr {
+                let gain = self.gain();
+                let mut r_array = $out_arr::new_from_i32(0);
+                for n in 0..r_array.data.len() {
+                    // Integrators, running at the input rate.
+                    for r in 0..$R {
+                        let mut temp = input[n*$R+r].to_bits() as i64;
+                        for integrator in self.integrators.iter_mut() {
+                            *integrator = integrator.wrapping_add(temp);
+                            temp = *integrator;
+                        }
+                    }
+                    // Combs, running at the output rate.
+                    let mut temp = self.integrators[$M-1];
+                    for comb in self.combs.iter_mut() {
+                        let delayed = comb[self.comb_index];
+                        comb[self.comb_index] = temp;
+                        temp = temp.wrapping_sub(delayed);
+                    }
+                    self.comb_index = (self.comb_index+1)%$D;
+                    r_array[n] = <$T>::from_bits( (temp/gain) as _ );
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+/// This macro implements a cascaded integrator-comb (CIC) interpolator.
+///
+/// The filter consists of M comb stages with a differential delay D running at the input rate, followed by
+/// zero-stuffing by R, and M integrators running at the output rate.
+/// The registers are 64 bit integers with wrapping arithmetic, so no intermediate saturation is needed.
+/// The output is normalized by the DC gain `(R*D)^M/R`.
+///
+/// The state of the filter is kept across blocks.
+///
+/// ## Arguments
+/// * `name`    - The name of the filter type. E.g. Cic.
+/// * `in_arr`  - The name of the input array type. E.g. Arr4.
+/// * `out_arr` - The name of the output array type. E.g. Arr16. Must be R times longer than the input array.
+/// * `R`       - The interpolation ratio.
+/// * `M`       - The order of the filter, the number of comb and integrator stages.
+/// * `D`       - The differential delay of the comb stages. Usually 1 or 2.
+/// * `T`       - The fixed type of the elements.
+///
+/// The input word length plus the bit growth `ceil( M*log2(R*D) )` must not exceed 64 bits.
+///
+/// # `::interpolate`
+/// Interpolate and filter a block of samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_array_real!( Arr8, 8, FixedI32<U20> );
+/// ia::declare_cic_interpolator!( Cic, Arr4, Arr8, 2, 2, 1, FixedI32<U20> );
+///
+/// let mut cic = Cic::new();
+/// let x = Arr4::new_from_i32( 2 );
+/// let y = cic.interpolate( &x );
+/// assert_eq!{ y.as_array_f32(), [1.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0, 2.0] };
+/// ```
+///
+/// # `::gain`, `::bit_growth` and `::reset`
+/// See the CIC decimator.
+#[macro_export]
+macro_rules! declare_cic_interpolator{
+    ( $name:ident, $in_arr:ident, $out_arr:ident, $R:expr, $M:expr, $D:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// CIC interpolator.
+        pub struct $name{
+            integrators: [i64; $M],
+            combs: [[i64; $D]; $M],
+            comb_index: usize,
+        }
+
+        impl $name {
+            /// Generate a filter with cleared state.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                $name {
+                    integrators: [0; $M],
+                    combs: [[0; $D]; $M],
+                    comb_index: 0,
+                }
+            }
+            /// Clear the state of the filter.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new();
+            }
+            /// Returns the DC gain of the filter.
+            #[allow(dead_code)]
+            fn gain( &self ) -> i64 {
+                return integer_array::filter::cic::gain( $R, $M, $D )/$R;
+            }
+            /// Returns the bit growth of the filter registers.
+            #[allow(dead_code)]
+            fn bit_growth( &self ) -> u32 {
+                return integer_array::filter::cic::bit_growth( $R, $M, $D );
+            }
+            /// Interpolate and filter a block of samples.
+            #[allow(dead_code)]
+            fn interpolate( &mut self, input: &$in_arr ) -> $out_arr {
+                let gain = self.gain();
+                let mut r_array = $out_arr::new_from_i32(0);
+                for n in 0..input.data.len() {
+                    // Combs, running at the input rate.
+                    let mut temp = input[n].to_bits() as i64;
+                    for comb in self.combs.iter_mut() {
+                        let delayed = comb[self.comb_index];
+                        comb[self.comb_index] = temp;
+                        temp = temp.wrapping_sub(delayed);
+                    }
+                    self.comb_index = (self.comb_index+1)%$D;
+                    // Zero-stuffing and integrators, running at the output rate.
+                    for r in 0..$R {
+                        let mut sample = if r == 0 { temp } else { 0 };
+                        for integrator in self.integrators.iter_mut() {
+                            *integrator = integrator.wrapping_add(sample);
+                            sample = *integrator;
+                        }
+                        r_array[n*$R+r] = <$T>::from_bits( (sample/gain) as _ );
+                    }
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn decimator_wraps() {
+        use crate as integer_array;
+        use fixed::{types::extra::U24, FixedI32};
+
+        // Full scale DC input, where the register growth is handled through wrapping.
+        integer_array::declare_array_real!( Arr32, 32, FixedI32<U24> );
+        integer_array::declare_array_real!( Arr4, 4, FixedI32<U24> );
+        integer_array::declare_cic_decimator!( Cic, Arr32, Arr4, 8, 6, 2, FixedI32<U24> );
+        let mut cic = Cic::new();
+        assert!{ cic.bit_growth()+32 <= 64 };
+        let x = Arr32::new_from_f32( -127.5 );
+        let blocks = 64;
+        for _i in 0..blocks {
+            cic.decimate( &x );
+        }
+        assert_eq!{ cic.decimate( &x ).as_array_f32(), [-127.5, -127.5, -127.5, -127.5] };
+
+        // Without wrapping, the last integrator would hold the input times the binomial coefficient C(n+M-1, M), far beyond i64.
+        let n = (32*(blocks+1)) as f64;
+        let mut unwrapped = x[0].to_bits() as f64;
+        for k in 1..=6 {
+            unwrapped *= (n+k as f64-1.0)/k as f64;
+        }
+        assert!{ (i64::MAX as f64)*1e6 < unwrapped.abs() };
+    }
+
+    #[test]
+    fn compensator_flattens_passband() {
+        use fixed::{types::extra::U20, FixedI32};
+
+        let mut taps = [FixedI32::<U20>::from_num(0); 21];
+        super::compensator( &mut taps, 8, 4, 1, FixedI32::<U20>::from_num(0.2) );
+        for frequency in [0.05f64, 0.1, 0.15].iter() {
+            let mut response = 0f64;
+            for (n, tap) in taps.iter().enumerate() {
+                response += tap.to_num::<f64>()*(2.0*core::f64::consts::PI*frequency*(n as f64-10.0)).cos();
+            }
+            let droop = super::magnitude( FixedI32::<U20>::from_num(*frequency), 8, 4, 1 ).to_num::<f64>();
+            assert!( (response*droop-1.0).abs() < 0.05 );
+        }
+    }
+}
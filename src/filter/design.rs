@@ -0,0 +1,322 @@
+use fixed::traits::FixedSigned;
+use crate::window::{self, Window};
+use crate::utility::{self, fixed_powi};
+
+/// Offset of a tap from the center of the filter, in samples.
+fn offset<T>( index:usize, length:usize ) -> T
+    where T: FixedSigned
+{
+    return T::from_num(index)-T::from_num(length-1)/T::from_num(2);
+}
+
+/// Normalized sinc function `sin(πx)/(πx)`.
+fn sinc<T>( x:T ) -> T
+    where T: FixedSigned
+{
+    let pi = T::from_num(fixed::consts::PI);
+    if x == T::from_num(0)
+    {
+        return T::from_num(1);
+    }
+    return utility::sin_precise_fixed( pi*x )/(pi*x);
+}
+
+/// Ideal lowpass impulse response `sin(2πft)/(πt)` at offset t.
+fn ideal_lowpass<T>( t:T, cutoff:T ) -> T
+    where T: FixedSigned
+{
+    let pi = T::from_num(fixed::consts::PI);
+    if t == T::from_num(0)
+    {
+        return cutoff*T::from_num(2);
+    }
+    return utility::sin_precise_fixed( pi*cutoff*t*T::from_num(2) )/(pi*t);
+}
+
+/// Normalize the taps to unity gain at a frequency, using the quantized taps.
+///
+/// The taps are first scaled by the inverse of the gain.
+/// The gain of the scaled and quantized taps is then calculated, and the residual error is added to the center tap(s).
+///
+/// ## Arguments
+/// * `taps`      - The taps to normalize.
+/// * `frequency` - The frequency at which the gain is normalized, relative to the sample rate.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::filter::design;
+///
+/// let mut taps = [FixedI32::<U20>::from_num(0.3); 3];
+/// design::normalize_gain( &mut taps, FixedI32::<U20>::from_num(0) );
+/// assert_eq!{ taps[0]+taps[1]+taps[2], 1 };
+/// ```
+pub fn normalize_gain<T>( taps:&mut [T], frequency:T )
+    where T: FixedSigned
+{
+    let length = taps.len();
+    let tau    = T::from_num(fixed::consts::TAU);
+
+    let gain = |taps:&[T]| -> T {
+        let mut temp = T::from_num(0);
+        for (n, tap) in taps.iter().enumerate() {
+            temp += *tap*utility::cos_precise_fixed( tau*frequency*offset::<T>(n, length) );
+        }
+        return temp;
+    };
+
+    let scale = T::from_num(1)/gain(taps);
+    for tap in taps.iter_mut() {
+        *tap *= scale;
+    }
+
+    let residual = T::from_num(1)-gain(taps);
+    if length%2 == 1
+    {
+        taps[length/2] += residual;
+    }
+    else
+    {
+        // The two center taps are half a sample from the center.
+        let weight = utility::cos_precise_fixed( tau*frequency/T::from_num(2) )*T::from_num(2);
+        if weight != T::from_num(0)
+        {
+            taps[length/2-1] += residual/weight;
+            taps[length/2]   += residual/weight;
+        }
+    }
+}
+
+/// Design a windowed-sinc lowpass filter, normalized to unity gain at DC.
+///
+/// ## Arguments
+/// * `taps`   - The taps to be designed.
+/// * `cutoff` - The cutoff frequency relative to the sample rate, in the 0<f<0.5 range.
+/// * `window` - The window applied to the ideal response.
+pub fn lowpass<T>( taps:&mut [T], cutoff:T, window:Window )
+    where T: FixedSigned
+{
+    let length = taps.len();
+    for (n, tap) in taps.iter_mut().enumerate() {
+        *tap = ideal_lowpass( offset(n, length), cutoff )*window::coefficient::<T>( window, n, length );
+    }
+    normalize_gain( taps, T::from_num(0) );
+}
+
+/// Design a windowed-sinc highpass filter through spectral inversion, normalized to unity gain at the Nyquist frequency.
+///
+/// ## Arguments
+/// * `taps`   - The taps to be designed. Must be of odd length, or the function panics.
+/// * `cutoff` - The cutoff frequency relative to the sample rate, in the 0<f<0.5 range.
+/// * `window` - The window applied to the ideal response.
+pub fn highpass<T>( taps:&mut [T], cutoff:T, window:Window )
+    where T: FixedSigned
+{
+    let length = taps.len();
+    // An even length has a forced zero at the Nyquist frequency.
+    assert!{ length%2 == 1 };
+    for (n, tap) in taps.iter_mut().enumerate() {
+        let t:T = offset(n, length);
+        let mut temp = -ideal_lowpass( t, cutoff );
+        if t == T::from_num(0)
+        {
+            temp += T::from_num(1);
+        }
+        *tap = temp*window::coefficient::<T>( window, n, length );
+    }
+    normalize_gain( taps, T::from_num(0.5f32) );
+}
+
+/// Design a windowed-sinc bandpass filter, normalized to unity gain at the center of the passband.
+///
+/// ## Arguments
+/// * `taps`   - The taps to be designed.
+/// * `low`    - The lower cutoff frequency relative to the sample rate.
+/// * `high`   - The upper cutoff frequency relative to the sample rate.
+/// * `window` - The window applied to the ideal response.
+pub fn bandpass<T>( taps:&mut [T], low:T, high:T, window:Window )
+    where T: FixedSigned
+{
+    let length = taps.len();
+    for (n, tap) in taps.iter_mut().enumerate() {
+        let t:T = offset(n, length);
+        *tap = ( ideal_lowpass( t, high )-ideal_lowpass( t, low ) )*window::coefficient::<T>( window, n, length );
+    }
+    normalize_gain( taps, (low+high)/T::from_num(2) );
+}
+
+/// Design a windowed-sinc bandstop filter, normalized to unity gain at DC.
+///
+/// ## Arguments
+/// * `taps`   - The taps to be designed. Must be of odd length, or the function panics.
+/// * `low`    - The lower cutoff frequency relative to the sample rate.
+/// * `high`   - The upper cutoff frequency relative to the sample rate.
+/// * `window` - The window applied to the ideal response.
+pub fn bandstop<T>( taps:&mut [T], low:T, high:T, window:Window )
+    where T: FixedSigned
+{
+    let length = taps.len();
+    // An even length has a forced zero at the Nyquist frequency.
+    assert!{ length%2 == 1 };
+    for (n, tap) in taps.iter_mut().enumerate() {
+        let t:T = offset(n, length);
+        let mut temp = ideal_lowpass( t, low )-ideal_lowpass( t, high );
+        if t == T::from_num(0)
+        {
+            temp += T::from_num(1);
+        }
+        *tap = temp*window::coefficient::<T>( window, n, length );
+    }
+    normalize_gain( taps, T::from_num(0) );
+}
+
+/// Design a raised-cosine pulse shape.
+///
+/// `h(t) = sinc(t)cos(πβt)/(1-(2βt)^2)`, where t is the time in symbols.
+///
+/// The pulse is a Nyquist pulse with a center tap of one, so symbols are preserved at the symbol instants.
+///
+/// ## Arguments
+/// * `taps`               - The taps to be designed. Should be of odd length.
+/// * `rolloff`            - The roll-off factor β, in the 0=<β<=1 range.
+/// * `samples_per_symbol` - The oversampling factor.
+pub fn raised_cosine<T>( taps:&mut [T], rolloff:T, samples_per_symbol:usize )
+    where T: FixedSigned
+{
+    let length = taps.len();
+    let pi     = T::from_num(fixed::consts::PI);
+    let one    = T::from_num(1);
+
+    for (n, tap) in taps.iter_mut().enumerate() {
+        let t:T = offset::<T>(n, length)/T::from_num(samples_per_symbol);
+        let denominator = one-fixed_powi( rolloff*t*T::from_num(2), 2 );
+        if denominator.abs() < T::from_num(0.001f32)
+        {
+            // The limit at t = ±1/(2β).
+            *tap = pi/T::from_num(4)*sinc( one/(rolloff*T::from_num(2)) );
+        }
+        else
+        {
+            *tap = sinc(t)*utility::cos_precise_fixed( pi*rolloff*t )/denominator;
+        }
+    }
+}
+
+/// Design a root-raised-cosine pulse shape, normalized to unit energy.
+///
+/// A pair of root-raised-cosine filters with unit energy preserves the symbols at the symbol instants.
+///
+/// ## Arguments
+/// * `taps`               - The taps to be designed. Should be of odd length.
+/// * `rolloff`            - The roll-off factor β, in the 0<β<=1 range.
+/// * `samples_per_symbol` - The oversampling factor.
+pub fn root_raised_cosine<T>( taps:&mut [T], rolloff:T, samples_per_symbol:usize )
+    where T: FixedSigned
+{
+    let length = taps.len();
+    let pi     = T::from_num(fixed::consts::PI);
+    let one    = T::from_num(1);
+    let four   = T::from_num(4);
+
+    let mut energy = T::from_num(0);
+    for (n, tap) in taps.iter_mut().enumerate() {
+        let t:T = offset::<T>(n, length)/T::from_num(samples_per_symbol);
+        let denominator = one-fixed_powi( rolloff*t*four, 2 );
+        if t == T::from_num(0)
+        {
+            *tap = one-rolloff+rolloff*four/pi;
+        }
+        else if denominator.abs() < T::from_num(0.001f32)
+        {
+            // The limit at t = ±1/(4β).
+            let phase = pi/(rolloff*four);
+            *tap = rolloff*T::from_num(fixed::consts::FRAC_1_SQRT_2)
+                    *( (one+T::from_num(2)/pi)*utility::sin_precise_fixed(phase)
+                      +(one-T::from_num(2)/pi)*utility::cos_precise_fixed(phase) );
+        }
+        else
+        {
+            let numerator = utility::sin_precise_fixed( pi*t*(one-rolloff) )
+                            +rolloff*four*t*utility::cos_precise_fixed( pi*t*(one+rolloff) );
+            *tap = numerator/(pi*t*denominator);
+        }
+        energy += *tap**tap;
+    }
+
+    let scale = one/crate::utility::sqrt( energy, T::from_num(0.001f32) );
+    for tap in taps.iter_mut() {
+        *tap *= scale;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fixed::{types::extra::U20, FixedI32};
+    use crate::window::Window;
+
+    /// Magnitude response of the taps at a frequency, in floating point.
+    fn response( taps:&[FixedI32<U20>], frequency:f64 ) -> f64 {
+        let mut re = 0f64;
+        let mut im = 0f64;
+        for (n, tap) in taps.iter().enumerate() {
+            let phase = 2.0*core::f64::consts::PI*frequency*n as f64;
+            re += tap.to_num::<f64>()*phase.cos();
+            im -= tap.to_num::<f64>()*phase.sin();
+        }
+        return (re*re+im*im).sqrt();
+    }
+
+    #[test]
+    fn lowpass_and_highpass() {
+        let mut taps = [FixedI32::<U20>::from_num(0); 31];
+        super::lowpass( &mut taps, FixedI32::<U20>::from_num(0.2), Window::Hamming );
+        assert!( (response(&taps, 0.05)-1.0).abs() < 0.01 );
+        assert!( response(&taps, 0.35) < 0.01 );
+
+        super::highpass( &mut taps, FixedI32::<U20>::from_num(0.2), Window::Blackman );
+        assert!( (response(&taps, 0.45)-1.0).abs() < 0.01 );
+        assert!( response(&taps, 0.05) < 0.01 );
+    }
+
+    #[test]
+    fn bandpass_and_bandstop() {
+        let mut taps = [FixedI32::<U20>::from_num(0); 41];
+        super::bandpass( &mut taps, FixedI32::<U20>::from_num(0.15), FixedI32::<U20>::from_num(0.3), Window::Hann );
+        assert!( (response(&taps, 0.225)-1.0).abs() < 0.01 );
+        assert!( response(&taps, 0.02) < 0.01 );
+        assert!( response(&taps, 0.45) < 0.01 );
+
+        super::bandstop( &mut taps, FixedI32::<U20>::from_num(0.15), FixedI32::<U20>::from_num(0.3), Window::Hann );
+        assert!( (response(&taps, 0.02)-1.0).abs() < 0.01 );
+        assert!( response(&taps, 0.225) < 0.01 );
+    }
+
+    #[test]
+    #[should_panic]
+    fn even_length_highpass_panics() {
+        let mut taps = [FixedI32::<U20>::from_num(0); 30];
+        super::highpass( &mut taps, FixedI32::<U20>::from_num(0.2), Window::Hamming );
+    }
+
+    #[test]
+    fn root_raised_cosine_pair_is_nyquist() {
+        let mut taps = [FixedI32::<U20>::from_num(0); 33];
+        super::root_raised_cosine( &mut taps, FixedI32::<U20>::from_num(0.35), 4 );
+        // The cascade of two filters is sampled at the symbol instants.
+        for shift in [0usize, 4, 8].iter() {
+            let mut temp = FixedI32::<U20>::from_num(0);
+            for n in *shift..33 {
+                temp += taps[n]*taps[n-shift];
+            }
+            if *shift == 0
+            {
+                assert!( (temp.to_num::<f64>()-1.0).abs() < 0.01 );
+            }
+            else
+            {
+                assert!( temp.to_num::<f64>().abs() < 0.02 );
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use fixed::traits::FixedSigned;
+use crate::window::{self, Window};
+
+/// Design the non-zero taps of a half-band lowpass filter.
+///
+/// A half-band filter of length `4K-1` has a center tap of 0.5, and every second tap from the center is zero.
+/// The remaining taps are symmetric, so only the `K` taps at odd offsets `1, 3, .., 2K-1` from the center are designed.
+/// The taps are a Hamming windowed sinc.
+/// The largest tap is adjusted after quantization so that the DC gain of the filter is exactly one.
+///
+/// ## Arguments
+/// * `taps` - The K one-sided taps to be designed, starting closest to the center tap.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::filter::halfband;
+///
+/// let mut taps = [FixedI32::<U20>::from_num(0); 4];
+/// halfband::taps( &mut taps );
+/// assert_eq!{ taps[0]+taps[1]+taps[2]+taps[3], 0.25 };
+/// ```
+pub fn taps<T>( taps:&mut [T] )
+    where T: FixedSigned
+{
+    let length = 4*taps.len()-1;
+    let pi = T::from_num(fixed::consts::PI);
+
+    let mut sum:T = T::from_num(0);
+    for (j, tap) in taps.iter_mut().enumerate() {
+        let offset = 2*j+1;
+        // sin(πk/2)/(πk) is ±1/(πk) for odd k.
+        let mut temp = T::from_num(1)/(pi*T::from_num(offset));
+        if j%2 == 1
+        {
+            temp = -temp;
+        }
+        let window:T = window::coefficient( Window::Hamming, length/2+offset, length );
+        *tap = temp*window;
+        sum += *tap;
+    }
+    // The taps on both sides sum to 0.5.
+    taps[0] += T::from_num(0.25f32)-sum;
+}
+
+/// This macro implements a half-band decimate-by-two filter.
+///
+/// The filter is implemented in polyphase form.
+/// The even-indexed input samples only meet the center tap of 0.5, and are merely delayed.
+/// The odd-indexed input samples are filtered by the symmetric non-zero taps.
+/// The zero taps are never multiplied, and the symmetric taps share a multiplication.
+///
+/// The state of the filter is kept across blocks.
+///
+/// ## Arguments
+/// * `name`    - The name of the filter type. E.g. HalfBand.
+/// * `in_arr`  - The name of the input array type. E.g. Arr16.
+/// * `out_arr` - The name of the output array type. E.g. Arr8. Must be half the length of the input array.
+/// * `K`       - The number of non-zero taps on each side of the center tap. The filter length is `4K-1`.
+/// * `T`       - The fixed type of the elements.
+///
+/// # `::new` and `::new_from_taps`
+/// The filter is initialized with taps from `filter::halfband::taps`, or with custom one-sided taps.
+///
+/// # `::decimate`
+/// Filter and decimate a block of samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr16, 16, FixedI32<U20> );
+/// ia::declare_array_real!( Arr8, 8, FixedI32<U20> );
+/// ia::declare_halfband_decimator!( HalfBand, Arr16, Arr8, 3, FixedI32<U20> );
+///
+/// let mut filter = HalfBand::new();
+/// let x = Arr16::new_from_i32( 2 );
+/// filter.decimate( &x );
+/// let y = filter.decimate( &x );
+/// assert_eq!{ y.as_array_i32(), [2, 2, 2, 2, 2, 2, 2, 2] };
+/// ```
+///
+/// # `::reset`
+/// Clear the state of the filter.
+#[macro_export]
+macro_rules! declare_halfband_decimator{
+    ( $name:ident, $in_arr:ident, $out_arr:ident, $K:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Half-band decimator.
+        pub struct $name{
+            taps: [$T; $K],
+            odd_line: [$T; 2*$K],
+            even_line: [$T; $K],
+        }
+
+        impl $name {
+            /// Generate a filter with designed taps and cleared state.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                let mut taps = [<$T>::from_num(0); $K];
+                integer_array::filter::halfband::taps( &mut taps );
+                return Self::new_from_taps( taps );
+            }
+            /// Generate a filter with the one-sided non-zero taps, starting closest to the center tap.
+            #[allow(dead_code)]
+            fn new_from_taps( taps: [$T; $K] ) -> Self {
+                $name {
+                    taps,
+                    odd_line: [<$T>::from_num(0); 2*$K],
+                    even_line: [<$T>::from_num(0); $K],
+                }
+            }
+            /// Clear the state of the filter.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new_from_taps( self.taps );
+            }
+            /// Filter and decimate a block of samples.
+            #[allow(dead_code)]
+            fn decimate( &mut self, input: &$in_arr ) -> $out_arr {
+                let even = input.even();
+                let odd  = input.odd();
+                let mut r_array = $out_arr::new_from_i32(0);
+                for m in 0..r_array.data.len() {
+                    self.odd_line.copy_within( 0..2*$K-1, 1 );
+                    self.odd_line[0] = odd[m];
+                    self.even_line.copy_within( 0..$K-1, 1 );
+                    self.even_line[0] = even[m];
+
+                    let mut temp = self.even_line[$K-1]/2;
+                    for j in 0..$K {
+                        temp += self.taps[j]*( self.odd_line[$K-1-j]+self.odd_line[$K+j] );
+                    }
+                    r_array[m] = temp;
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn decimator_rejects_nyquist() {
+        use crate as integer_array;
+        use fixed::{types::extra::U20, FixedI32};
+
+        integer_array::declare_array_real!( Arr32, 32, FixedI32<U20> );
+        integer_array::declare_array_real!( Arr16, 16, FixedI32<U20> );
+        integer_array::declare_halfband_decimator!( HalfBand, Arr32, Arr16, 5, FixedI32<U20> );
+
+        let mut x = Arr32::new_from_i32(1);
+        for n in 0..16 {
+            x[2*n+1] = -x[2*n+1];
+        }
+        let mut filter = HalfBand::new();
+        filter.decimate( &x );
+        let y = filter.decimate( &x );
+        for m in 0..16 {
+            assert!( y[m].abs() < 0.01 );
+        }
+    }
+}
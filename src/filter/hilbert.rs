@@ -0,0 +1,133 @@
+use fixed::traits::FixedSigned;
+use crate::window::{self, Window};
+
+/// Design the non-zero taps of an FIR Hilbert transformer.
+///
+/// A Hilbert transformer of length `4K-1` has zero-valued taps at every even offset from the center, including the center itself.
+/// The remaining taps are anti-symmetric, so only the `K` taps at odd offsets `1, 3, .., 2K-1` from the center are designed.
+/// The taps are the ideal response `2/(πk)`, tapered by a Hamming window.
+///
+/// ## Arguments
+/// * `taps` - The K one-sided taps to be designed, starting closest to the center.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::filter::hilbert;
+///
+/// let mut taps = [FixedI32::<U20>::from_num(0); 4];
+/// hilbert::taps( &mut taps );
+/// assert_eq!{ taps[0].to_num::<f32>(), 0.6076193 };
+/// ```
+pub fn taps<T>( taps:&mut [T] )
+    where T: FixedSigned
+{
+    let length = 4*taps.len()-1;
+    let pi = T::from_num(fixed::consts::PI);
+
+    for (j, tap) in taps.iter_mut().enumerate() {
+        let offset = 2*j+1;
+        let window:T = window::coefficient( Window::Hamming, length/2+offset, length );
+        *tap = T::from_num(2)/(pi*T::from_num(offset))*window;
+    }
+}
+
+/// This macro implements an FIR Hilbert transformer, which turns a real array into an analytic complex array.
+///
+/// The real part of the output is the input delayed by the group delay of the filter, `2K-1` samples.
+/// The imaginary part is the Hilbert transform of the input.
+/// Only the non-zero odd-offset taps are multiplied, and the anti-symmetric taps share a multiplication.
+///
+/// The state of the filter is kept across blocks.
+///
+/// ## Arguments
+/// * `name`        - The name of the filter type. E.g. Hilbert.
+/// * `real_arr`    - The name of the real input array type. E.g. Arr32.
+/// * `complex_arr` - The name of the complex output array type, of the same length. E.g. CArr32.
+/// * `K`           - The number of non-zero taps on each side of the center. The filter length is `4K-1`.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new` and `::new_from_taps`
+/// The filter is initialized with taps from `filter::hilbert::taps`, or with custom one-sided taps.
+///
+/// # `::analytic`
+/// Generate the analytic signal of a block of samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr32, Arr32, 32, FixedI32<U20> );
+/// ia::declare_hilbert_transformer!( Hilbert, Arr32, CArr32, 4, FixedI32<U20> );
+///
+/// // A tone at a quarter of the sample rate.
+/// let x = Arr32::new_from_f32_array( [1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0,
+///                                     1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0] );
+/// let mut filter = Hilbert::new();
+/// let z = filter.analytic( &x );
+///
+/// // Once the filter has settled, the imaginary part is the input shifted by a quarter period.
+/// assert_eq!{ z[21].re, -1 };
+/// assert!{ 0.95 < z[20].im && z[20].im < 1.05 };
+/// assert!{ -1.05 < z[22].im && z[22].im < -0.95 };
+/// ```
+///
+/// # `::reset`
+/// Clear the state of the filter.
+#[macro_export]
+macro_rules! declare_hilbert_transformer{
+    ( $name:ident, $real_arr:ident, $complex_arr:ident, $K:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// FIR Hilbert transformer.
+        pub struct $name{
+            taps: [$T; $K],
+            delay_line: [$T; 4*$K-1],
+        }
+
+        impl $name {
+            /// Generate a filter with designed taps and cleared state.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                let mut taps = [<$T>::from_num(0); $K];
+                integer_array::filter::hilbert::taps( &mut taps );
+                return Self::new_from_taps( taps );
+            }
+            /// Generate a filter with the one-sided non-zero taps, starting closest to the center.
+            #[allow(dead_code)]
+            fn new_from_taps( taps: [$T; $K] ) -> Self {
+                $name {
+                    taps,
+                    delay_line: [<$T>::from_num(0); 4*$K-1],
+                }
+            }
+            /// Clear the state of the filter.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new_from_taps( self.taps );
+            }
+            /// Generate the analytic signal of a block of samples.
+            #[allow(dead_code)]
+            fn analytic( &mut self, input: &$real_arr ) -> $complex_arr {
+                let center = 2*$K-1;
+                let mut r_array = $complex_arr::new_from_i32( 0, 0 );
+                for n in 0..input.data.len() {
+                    self.delay_line.copy_within( 0..4*$K-2, 1 );
+                    self.delay_line[0] = input[n];
+
+                    let mut temp = <$T>::from_num(0);
+                    for j in 0..$K {
+                        let offset = 2*j+1;
+                        temp += self.taps[j]*( self.delay_line[center+offset]-self.delay_line[center-offset] );
+                    }
+                    r_array.data[n] = num::complex::Complex::new( self.delay_line[center], temp );
+                }
+                return r_array;
+            }
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Histograms, cumulative distributions and quantiles of fixed-point data.
+//!
+//! Bin `i` covers `edges[i]=<x<edges[i+1]`, except the last bin, which includes its upper edge.
+//! Samples outside the edges are not counted. Counts are `u64`, so streaming histograms do not overflow.
+
+use fixed::traits::FixedSigned;
+use crate::utility::{wide_bits, from_wide_bits};
+
+/// Fill a buffer of B+1 edges with B uniform bins from `low` to `high`.
+///
+/// The edges are rounded up to the resolution of the type, so they agree exactly with `uniform_bin`.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::histogram;
+///
+/// let mut edges = [FixedI32::<U20>::from_num(0); 5];
+/// histogram::uniform_edges( FixedI32::<U20>::from_num(-1), FixedI32::<U20>::from_num(1), &mut edges );
+/// assert_eq!{ edges, [-1.0, -0.5, 0.0, 0.5, 1.0] };
+/// ```
+pub fn uniform_edges<T>( low:T, high:T, edges:&mut [T] )
+    where T: FixedSigned
+{
+    let bins  = (edges.len()-1) as i128;
+    let low   = wide_bits(low);
+    let width = wide_bits(high)-low;
+    for (i, edge) in edges.iter_mut().enumerate() {
+        *edge = from_wide_bits( low+((i as i128)*width+bins-1)/bins );
+    }
+}
+
+/// Find the bin of a value among `bins` uniform bins from `low` to `high`.
+///
+/// Returns `None` for values outside the range.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::histogram;
+///
+/// let low  = FixedI32::<U20>::from_num(-1);
+/// let high = FixedI32::<U20>::from_num(1);
+/// assert_eq!{ histogram::uniform_bin( FixedI32::<U20>::from_num(0.2), low, high, 4 ), Some(2) };
+/// assert_eq!{ histogram::uniform_bin( FixedI32::<U20>::from_num(1), low, high, 4 ), Some(3) };
+/// assert_eq!{ histogram::uniform_bin( FixedI32::<U20>::from_num(1.5), low, high, 4 ), None };
+/// ```
+pub fn uniform_bin<T>( value:T, low:T, high:T, bins:usize ) -> Option<usize>
+    where T: FixedSigned
+{
+    if value < low || high < value || bins == 0
+    {
+        return None;
+    }
+    let width = wide_bits(high)-wide_bits(low);
+    if width == 0
+    {
+        return Some(0);
+    }
+    let bin = ((wide_bits(value)-wide_bits(low))*(bins as i128)/width) as usize;
+    return Some( bin.min( bins-1 ) );
+}
+
+/// Find the bin of a value among the bins between ascending edges, by binary search.
+///
+/// Returns `None` for values outside the edges.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::histogram;
+///
+/// let edges = [FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(10)];
+/// assert_eq!{ histogram::edge_bin( FixedI32::<U20>::from_num(5), &edges ), Some(1) };
+/// assert_eq!{ histogram::edge_bin( FixedI32::<U20>::from_num(-5), &edges ), None };
+/// ```
+pub fn edge_bin<T>( value:T, edges:&[T] ) -> Option<usize>
+    where T: FixedSigned
+{
+    if edges.len() < 2 || value < edges[0] || edges[edges.len()-1] < value
+    {
+        return None;
+    }
+    let bin = edges.partition_point( |edge| *edge <= value )-1;
+    return Some( bin.min( edges.len()-2 ) );
+}
+
+/// Calculate the cumulative distribution of a histogram, the fraction of the counted samples up to and including each bin.
+///
+/// The distribution is zero for empty histograms.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::histogram;
+///
+/// let mut cdf = [FixedI32::<U20>::from_num(0); 4];
+/// histogram::cdf( &[1, 0, 2, 1], &mut cdf );
+/// assert_eq!{ cdf, [0.25, 0.25, 0.75, 1.0] };
+/// ```
+pub fn cdf<T>( counts:&[u64], cdf:&mut [T] )
+    where T: FixedSigned
+{
+    let total:u64 = counts.iter().sum();
+    let one = wide_bits( T::from_num(1) );
+    let mut cumulative:u64 = 0;
+    for (count, item) in counts.iter().zip( cdf.iter_mut() ) {
+        cumulative += count;
+        *item = if total == 0 { T::from_num(0) } else { from_wide_bits( one*(cumulative as i128)/(total as i128) ) };
+    }
+}
+
+/// Estimate the quantile `q` of the counted samples, in the 0..=1 range, from a histogram and its edges.
+///
+/// The samples are taken as spread evenly within their bins, so the quantile is interpolated linearly within the bin it falls in.
+/// Returns the lowest edge for empty histograms.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::histogram;
+///
+/// let mut edges = [FixedI32::<U20>::from_num(0); 5];
+/// histogram::uniform_edges( FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(4), &mut edges );
+/// let counts = [1, 0, 2, 1];
+/// assert_eq!{ histogram::quantile( &counts, &edges, FixedI32::<U20>::from_num(0.5) ), 2.5 };
+/// assert_eq!{ histogram::quantile( &counts, &edges, FixedI32::<U20>::from_num(1) ), 4 };
+/// ```
+pub fn quantile<T>( counts:&[u64], edges:&[T], q:T ) -> T
+    where T: FixedSigned
+{
+    let total:u64 = counts.iter().sum();
+    if total == 0
+    {
+        return edges[0];
+    }
+    let q = q.max( T::from_num(0) ).min( T::from_num(1) );
+    // The rank of the quantile, with the fractional bits of the type.
+    let rank = wide_bits(q)*(total as i128);
+    let one  = wide_bits( T::from_num(1) );
+    let mut before:i128 = 0;
+    for (bin, count) in counts.iter().enumerate() {
+        let after = before+(*count as i128)*one;
+        if rank <= after && *count != 0
+        {
+            let fraction:T = from_wide_bits( (rank-before)/(*count as i128) );
+            return edges[bin]+(edges[bin+1]-edges[bin])*fraction;
+        }
+        before = after;
+    }
+    return edges[counts.len()];
+}
+
+/// This macro implements a streaming histogram with B bins, which accumulates blocks without storing the samples.
+///
+/// ## Arguments
+/// * `name` - The name of the histogram type. E.g. Histogram16.
+/// * `B`    - The number of bins.
+/// * `T`    - The fixed type of the samples.
+///
+/// # `::new` and `::new_from_edges`
+/// Generate a histogram of uniform bins from `low` to `high`, or of the bins between B+1 ascending edges.
+///
+/// # `::push` and `::push_block`
+/// Count a sample, or a block of samples, e.g. the `data` of an array.
+///
+/// # `::counts`, `::total`, `::outside` and `::reset`
+/// Get the counts of the bins, the total count of the bins, or the number of samples outside the edges. Or clear the counts.
+///
+/// # `::cdf` and `::quantile`
+/// Get the cumulative distribution, or estimate a quantile in the 0..=1 range, see `histogram::cdf` and `histogram::quantile`.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_histogram!( Histogram4, 4, FixedI32<U20> );
+///
+/// let mut histogram = Histogram4::new( FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(4) );
+/// histogram.push_block( &Arr4::new_from_f32_array( [0.5, 2.5, 2.5, 9.0] ).data );
+/// histogram.push_block( &Arr4::new_from_f32_array( [3.5, 3.5, 1.5, 0.5] ).data );
+/// assert_eq!{ histogram.counts(), [2, 1, 2, 2] };
+/// assert_eq!{ histogram.outside(), 1 };
+/// assert_eq!{ histogram.quantile( FixedI32::<U20>::from_num(0.5) ).to_num::<f32>(), 2.25 };
+/// ```
+#[macro_export]
+macro_rules! declare_histogram{
+    ( $name:ident, $B:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Streaming histogram.
+        pub struct $name{
+            edges: [$T; $B+1],
+            counts: [u64; $B],
+            outside: u64,
+        }
+
+        impl $name {
+            /// Generate a histogram of uniform bins from `low` to `high`.
+            #[allow(dead_code)]
+            fn new( low:$T, high:$T ) -> Self {
+                let mut edges = [<$T>::from_num(0); $B+1];
+                integer_array::histogram::uniform_edges( low, high, &mut edges );
+                return Self::new_from_edges( edges );
+            }
+            /// Generate a histogram of the bins between ascending edges.
+            #[allow(dead_code)]
+            fn new_from_edges( edges:[$T; $B+1] ) -> Self {
+                $name {
+                    edges,
+                    counts: [0; $B],
+                    outside: 0,
+                }
+            }
+            /// Clear the counts.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.counts = [0; $B];
+                self.outside = 0;
+            }
+            /// Count a sample.
+            #[allow(dead_code)]
+            fn push( &mut self, item:$T ) {
+                match integer_array::histogram::edge_bin( item, &self.edges ) {
+                    Some(bin) => self.counts[bin] += 1,
+                    None      => self.outside += 1,
+                }
+            }
+            /// Count a block of samples.
+            #[allow(dead_code)]
+            fn push_block( &mut self, input:&[$T] ) {
+                for item in input.iter() {
+                    self.push( *item );
+                }
+            }
+            /// The counts of the bins.
+            #[allow(dead_code)]
+            fn counts( &self ) -> [u64; $B] {
+                return self.counts;
+            }
+            /// The total count of the bins.
+            #[allow(dead_code)]
+            fn total( &self ) -> u64 {
+                return self.counts.iter().sum();
+            }
+            /// The number of samples outside the edges.
+            #[allow(dead_code)]
+            fn outside( &self ) -> u64 {
+                return self.outside;
+            }
+            /// The cumulative distribution of the counted samples.
+            #[allow(dead_code)]
+            fn cdf( &self ) -> [$T; $B] {
+                let mut r_array = [<$T>::from_num(0); $B];
+                integer_array::histogram::cdf( &self.counts, &mut r_array );
+                return r_array;
+            }
+            /// Estimate the quantile `q` of the counted samples.
+            #[allow(dead_code)]
+            fn quantile( &self, q:$T ) -> $T {
+                return integer_array::histogram::quantile( &self.counts, &self.edges, q );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fixed::{types::extra::U20, FixedI32};
+
+    #[test]
+    fn uniform_bins_agree_with_their_edges() {
+        // Edges which do not fall on the resolution of the type.
+        let low  = FixedI32::<U20>::from_num(-1);
+        let high = FixedI32::<U20>::from_num(0.3);
+        let mut edges = [FixedI32::<U20>::from_num(0); 8];
+        super::uniform_edges( low, high, &mut edges );
+        let mut value = low-FixedI32::<U20>::DELTA*4;
+        while value <= high+FixedI32::<U20>::DELTA*4 {
+            assert_eq!{ super::uniform_bin( value, low, high, 7 ), super::edge_bin( value, &edges ) };
+            value += FixedI32::<U20>::DELTA*37;
+        }
+        for edge in edges.iter() {
+            for offset in -3..=3 {
+                let value = *edge+FixedI32::<U20>::DELTA*offset;
+                assert_eq!{ super::uniform_bin( value, low, high, 7 ), super::edge_bin( value, &edges ) };
+            }
+        }
+        assert_eq!{ super::uniform_bin( high, low, high, 7 ), Some(6) };
+    }
+}
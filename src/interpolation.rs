@@ -0,0 +1,285 @@
+//! Interpolation of real and complex samples at fractional positions, and a Farrow fractional-delay filter.
+//!
+//! Positions are given in samples from the first item, as a fixed-point number of the item type.
+//! Samples beyond the ends are taken as the nearest end sample.
+//! The weights are calculated in wide integers, and have the fractional bits of the type.
+
+use fixed::traits::{Fixed, FixedSigned};
+use crate::matrix::Element;
+use crate::utility::{wide_bits, from_wide_bits};
+
+/// The integer part of a position, and its fraction in [0, 1), as wide bits.
+fn split<T>( position:T ) -> (i128, i128)
+    where T: FixedSigned
+{
+    let bits = wide_bits(position);
+    return ( bits >> T::FRAC_NBITS, bits & ((1i128 << T::FRAC_NBITS)-1) );
+}
+
+/// The sample at an index, with the index clamped to the ends.
+fn sample<E>( data:&[E], index:i128 ) -> E
+    where E: Element
+{
+    return data[ index.clamp( 0, data.len() as i128-1 ) as usize ];
+}
+
+/// The sum of the samples from the index `first`, weighted by wide bits.
+fn weighted_sum<E>( data:&[E], first:i128, weights:&[i128] ) -> E
+    where E: Element
+{
+    let mut sum = E::zero();
+    for (k, weight) in weights.iter().enumerate() {
+        sum = sum.add( sample( data, first+k as i128 ).scale( from_wide_bits( *weight ) ) );
+    }
+    return sum;
+}
+
+/// Linear interpolation between the two samples around a fractional position.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::interpolation;
+///
+/// let x = [0.0, 1.0, 4.0, 9.0].map( FixedI32::<U20>::from_num );
+/// assert_eq!{ interpolation::linear( &x, FixedI32::<U20>::from_num(1.25) ), 1.75 };
+/// // Beyond the ends, the end samples are repeated.
+/// assert_eq!{ interpolation::linear( &x, FixedI32::<U20>::from_num(5) ), 9 };
+/// ```
+pub fn linear<E>( data:&[E], position:E::Real ) -> E
+    where E: Element
+{
+    let one = 1i128 << E::Real::FRAC_NBITS;
+    let (index, fraction) = split( position );
+    return weighted_sum( data, index, &[one-fraction, fraction] );
+}
+
+/// Cubic Hermite interpolation through the four samples around a fractional position.
+///
+/// The slopes at the samples are the central differences, as in the Catmull-Rom spline.
+/// The curve is continuous in value and slope, and passes exactly through quadratic polynomials.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::interpolation;
+///
+/// // Samples of x².
+/// let x = [0.0, 1.0, 4.0, 9.0, 16.0].map( FixedI32::<U20>::from_num );
+/// assert_eq!{ interpolation::cubic_hermite( &x, FixedI32::<U20>::from_num(2.5) ), 6.25 };
+/// ```
+pub fn cubic_hermite<E>( data:&[E], position:E::Real ) -> E
+    where E: Element
+{
+    let bits = E::Real::FRAC_NBITS;
+    let one = 1i128 << bits;
+    let (index, t) = split( position );
+    let t2 = (t*t) >> bits;
+    let t3 = (t2*t) >> bits;
+    let weights = [
+        (-t3+2*t2-t) >> 1,
+        (3*t3-5*t2+2*one) >> 1,
+        (-3*t3+4*t2+t) >> 1,
+        (t3-t2) >> 1,
+    ];
+    return weighted_sum( data, index-1, &weights );
+}
+
+/// Lagrange interpolation of an order through `order+1` samples around a fractional position.
+///
+/// The samples are centered on the interval of the position, with one more sample after it for even orders.
+/// Order 1 is linear interpolation, and order 3 is cubic. Polynomials up to the order are interpolated exactly.
+/// High orders ring near steps in the data, and need headroom in the type for the intermediate products.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::interpolation;
+///
+/// // Samples of x³.
+/// let x = [-8.0, -1.0, 0.0, 1.0, 8.0, 27.0].map( FixedI32::<U20>::from_num );
+/// assert_eq!{ interpolation::lagrange( &x, FixedI32::<U20>::from_num(2.5), 3 ), 0.125 };
+/// assert_eq!{ interpolation::lagrange( &x, FixedI32::<U20>::from_num(2.5), 1 ), 0.5 };
+/// ```
+pub fn lagrange<E>( data:&[E], position:E::Real, order:usize ) -> E
+    where E: Element
+{
+    assert!{ 0 < order };
+    let bits = E::Real::FRAC_NBITS;
+    let (index, fraction) = split( position );
+    let first = index-((order as i128-1) >> 1);
+    // The position relative to the first sample.
+    let t = ((index-first) << bits)+fraction;
+
+    let mut sum = E::zero();
+    for k in 0..=order as i128 {
+        let mut numerator = 1i128 << bits;
+        let mut denominator = 1i128;
+        for j in (0..=order as i128).filter( |j| *j != k ) {
+            numerator = (numerator*(t-(j << bits))) >> bits;
+            denominator *= k-j;
+        }
+        let weight:E::Real = from_wide_bits( numerator/denominator );
+        sum = sum.add( sample( data, first+k ).scale( weight ) );
+    }
+    return sum;
+}
+
+/// This macro implements a Farrow-structure fractional-delay filter, for real or complex items.
+///
+/// The filter is a cubic Lagrange interpolator over the four most recent inputs, written as a polynomial in the fraction μ.
+/// Four fixed branch filters are applied to the input, and their outputs are combined by Horner's method in μ.
+/// The delay can thus be changed for every sample, or every block, without redesigning any taps.
+///
+/// The delay is `1+μ` samples, for a fraction μ in [0, 1]. The interpolator is best at the center of its taps, which it reaches at μ = 0.5.
+/// The state of the filter is kept across blocks.
+///
+/// ## Arguments
+/// * `name` - The name of the filter type. E.g. Farrow.
+/// * `arr`  - The name of a real or complex array type, for blocks. E.g. Arr32 or CArr32.
+/// * `item` - The item type, `$T` or `num::complex::Complex<$T>`. Must be the item type of `arr`.
+///
+/// # `::new` and `::reset`
+/// Generate a filter with cleared state, or clear the state.
+///
+/// # `::push`
+/// Push a sample, and return the output delayed by `1+μ` samples.
+///
+/// # `::process`
+/// Delay a block of samples by `1+μ` samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr8, 8, FixedI32<U20> );
+/// ia::declare_farrow_delay!( Farrow, Arr8, FixedI32<U20> );
+///
+/// // A ramp, delayed by 1.25 samples.
+/// let mut farrow = Farrow::new();
+/// let y = farrow.process( &Arr8::ramp_from_f32( 0.0, 1.0 ), FixedI32::<U20>::from_num(0.25) );
+/// assert_eq!{ y[7], 5.75 };
+///
+/// // The delay changes at the next block.
+/// let y = farrow.process( &Arr8::ramp_from_f32( 8.0, 1.0 ), FixedI32::<U20>::from_num(0.75) );
+/// assert_eq!{ y[0], 6.25 };
+/// ```
+#[macro_export]
+macro_rules! declare_farrow_delay{
+    ( $name:ident, $arr:ident, $item:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Farrow fractional-delay filter.
+        pub struct $name{
+            // The three previous inputs, the newest first.
+            history: [$item; 3],
+        }
+
+        impl $name {
+            /// Generate a filter with cleared state.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                use integer_array::matrix::Element;
+                $name {
+                    history: [<$item>::zero(); 3],
+                }
+            }
+            /// Clear the state of the filter.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new();
+            }
+            /// Push a sample, and return the output delayed by `1+fraction` samples.
+            #[allow(dead_code)]
+            fn push( &mut self, item:$item, fraction:<$item as integer_array::matrix::Element>::Real ) -> $item {
+                use integer_array::matrix::Element;
+                type Real = <$item as Element>::Real;
+                let half = Real::from_num(0.5);
+                let third = Real::from_num(1.0/3.0);
+                let sixth = Real::from_num(1.0/6.0);
+                let (x0, [x1, x2, x3]) = (item, self.history);
+
+                // The branch filters, from the expansion of the cubic Lagrange weights in the fraction.
+                let c1 = Element::sub( Element::sub( x2, Element::scale( x0, third ) ), Element::add( Element::scale( x1, half ), Element::scale( x3, sixth ) ) );
+                let c2 = Element::sub( Element::scale( Element::add( x0, x2 ), half ), x1 );
+                let c3 = Element::add( Element::scale( Element::sub( x1, x2 ), half ), Element::scale( Element::sub( x3, x0 ), sixth ) );
+                let mut r_item = Element::add( Element::scale( c3, fraction ), c2 );
+                r_item = Element::add( Element::scale( r_item, fraction ), c1 );
+                r_item = Element::add( Element::scale( r_item, fraction ), x1 );
+
+                self.history = [x0, x1, x2];
+                return r_item;
+            }
+            /// Delay a block of samples by `1+fraction` samples.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$arr, fraction:<$item as integer_array::matrix::Element>::Real ) -> $arr {
+                let mut r_array = *input;
+                for item in r_array.data.iter_mut() {
+                    *item = self.push( *item, fraction );
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+    use num::complex::Complex;
+
+    type T = FixedI32<U20>;
+
+    #[test]
+    fn interpolators_reproduce_polynomials() {
+        // Samples of 1 - x + x²/4 - x³/16.
+        let p = |x:f64| 1.0-x+x*x/4.0-x*x*x/16.0;
+        let samples:[T; 9] = core::array::from_fn( |n| T::from_num( p( n as f64 ) ) );
+        let complex:[Complex<T>; 9] = samples.map( |x| Complex::new( x, -x ) );
+        // Positions where all the samples of the highest order are within the data.
+        for step in 16..48 {
+            let position = step as f64/8.0;
+            let x = T::from_num( position );
+            for order in [3, 4, 5] {
+                let y = super::lagrange( &samples, x, order );
+                assert!{ (y.to_num::<f64>()-p( position )).abs() < 0.0001 };
+            }
+            let z = super::lagrange( &complex, x, 3 );
+            assert!{ (z.re.to_num::<f64>()-p( position )).abs() < 0.0001 && z.re == -z.im };
+
+            // Cubic Hermite is exact to second order, and linear to first order.
+            let q = |x:f64| 1.0-x+x*x/4.0;
+            let quadratic:[T; 9] = core::array::from_fn( |n| T::from_num( q( n as f64 ) ) );
+            assert!{ (super::cubic_hermite( &quadratic, x ).to_num::<f64>()-q( position )).abs() < 0.0001 };
+            let line:[T; 9] = core::array::from_fn( |n| T::from_num( 1.0-n as f64/2.0 ) );
+            assert!{ (super::linear( &line, x ).to_num::<f64>()-(1.0-position/2.0)).abs() < 0.0001 };
+        }
+    }
+
+    #[test]
+    fn farrow_delays_a_tone() {
+        integer_array::declare_array_complex!( CArr32, Arr32, 32, T );
+        integer_array::declare_farrow_delay!( Farrow, CArr32, Complex<T> );
+
+        let frequency = 0.05;
+        let tone = |t:f64| Complex::new( T::from_num( (2.0*core::f64::consts::PI*frequency*t).cos() ), T::from_num( (2.0*core::f64::consts::PI*frequency*t).sin() ) );
+        let mut farrow = Farrow::new();
+        for (block, fraction) in [0.0, 0.3, 0.5, 1.0].iter().enumerate() {
+            let input = CArr32 { data: core::array::from_fn( |n| tone( (32*block+n) as f64 ) ) };
+            let output = farrow.process( &input, T::from_num( *fraction ) );
+            for (n, item) in output.data.iter().enumerate().skip( 3 ) {
+                let expected = tone( (32*block+n) as f64-1.0-fraction );
+                assert!{ (item.re-expected.re).abs() < 0.001 && (item.im-expected.im).abs() < 0.001 };
+            }
+        }
+        farrow.reset();
+        assert_eq!{ farrow, Farrow::new() };
+    }
+}
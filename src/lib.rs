@@ -32,9 +32,11 @@
 
 #![crate_name = "integer_array"]
 #![no_std]
+// Explicit returns are used throughout the crate.
+#![allow(clippy::needless_return)]
 
 // Use std for test.
-#[cfg(all(not(feature = "std"), test))]
+#[cfg(test)]
 extern crate std;
 
 // Pull in core as std.
@@ -48,4 +50,21 @@ pub mod utility;
 pub mod trait_definitions;
 pub mod real;
 pub mod complex;
-pub mod filter;
\ No newline at end of file
+pub mod filter;
+pub mod window;
+pub mod agc;
+pub mod pll;
+pub mod modulation;
+pub mod demodulation;
+pub mod fft;
+pub mod table;
+pub mod spectral;
+pub mod buffer;
+pub mod moving;
+pub mod histogram;
+pub mod matrix;
+pub mod linalg;
+pub mod polynomial;
+pub mod interpolation;
+pub mod timing;
+pub mod preamble;
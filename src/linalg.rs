@@ -0,0 +1,501 @@
+//! Small linear solvers on fixed-size matrices: LU, Cholesky and QR.
+//!
+//! The solvers work on `matrix::Matrix` of real or complex fixed-point elements, and are intended for small systems of up to about 16×16.
+//! Each factorization checks its pivots against the largest element of the matrix.
+//! A pivot of zero means the matrix is singular.
+//! A pivot smaller than the largest element by more than half the fractional bits of the type means it is ill-conditioned,
+//! as the solution would keep less than half of its resolution.
+//! Both are reported as a `SolveError`, as is an intermediate value or a solution outside the range of the type, rather than saturated.
+
+use crate::matrix::{Element, Matrix};
+use crate::utility::{wide_bits, from_wide_bits};
+use fixed::traits::{Fixed, FixedSigned};
+
+/// The reason a linear system could not be factorized or solved.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SolveError {
+    /// A pivot is zero, so the matrix is singular.
+    Singular,
+    /// A pivot is too small relative to the largest element of the matrix to keep half the fractional bits of the type.
+    IllConditioned,
+    /// A diagonal element of the Cholesky factorization is not positive.
+    NotPositiveDefinite,
+    /// An intermediate value or the solution is outside the range of the type.
+    Overflow,
+}
+
+/// The largest magnitude of the elements of a matrix.
+fn max_abs<E, const R:usize, const C:usize>( matrix:&Matrix<E, R, C> ) -> E::Real
+    where E: Element
+{
+    let mut r_max = E::Real::ZERO;
+    for item in matrix.data.iter().flatten() {
+        r_max = r_max.max( item.abs() );
+    }
+    return r_max;
+}
+
+/// Check the magnitude of a pivot against the largest magnitude of the matrix.
+fn check_pivot<T>( pivot:T, scale:T ) -> Result<(), SolveError>
+    where T: FixedSigned
+{
+    if pivot <= 0
+    {
+        return Err( SolveError::Singular );
+    }
+    if (wide_bits(pivot) << (T::FRAC_NBITS/2)) < wide_bits(scale)
+    {
+        return Err( SolveError::IllConditioned );
+    }
+    return Ok(());
+}
+
+/// `accumulator - a·b`, or an overflow error.
+fn mul_sub<E>( accumulator:E, a:E, b:E ) -> Result<E, SolveError>
+    where E: Element
+{
+    let product = a.checked_mul( b ).ok_or( SolveError::Overflow )?;
+    return accumulator.checked_sub( product ).ok_or( SolveError::Overflow );
+}
+
+/// `numerator/denominator`, or an overflow error.
+fn div<E>( numerator:E, denominator:E ) -> Result<E, SolveError>
+    where E: Element
+{
+    return numerator.checked_div( denominator ).ok_or( SolveError::Overflow );
+}
+
+/// The square root of a non-negative value, rounded down.
+fn sqrt<T>( value:T ) -> T
+    where T: FixedSigned
+{
+    let bits = wide_bits(value).max(0) as u128;
+    if T::FRAC_NBITS <= bits.leading_zeros()
+    {
+        return from_wide_bits( (bits << T::FRAC_NBITS).isqrt() as i128 );
+    }
+    // Values of the widest types with most of their bits fractional, at half the resolution.
+    return from_wide_bits( (bits.isqrt() << (T::FRAC_NBITS/2)) as i128 );
+}
+
+/// `sqrt(a²+b²)` of two non-negative values, or an overflow error.
+fn hypot<T>( a:T, b:T ) -> Result<T, SolveError>
+    where T: FixedSigned
+{
+    let (a, b) = (wide_bits(a) as u128, wide_bits(b) as u128);
+    let power = a.checked_mul( a ).and_then( |x| x.checked_add( b.checked_mul( b )? ) ).ok_or( SolveError::Overflow )?;
+    let bits = power.isqrt() as i128;
+    if wide_bits(T::MAX) < bits
+    {
+        return Err( SolveError::Overflow );
+    }
+    return Ok( from_wide_bits( bits ) );
+}
+
+/// The LU factorization `P·A = L·U` of a square matrix, with partial pivoting.
+///
+/// `L` is unit lower triangular, and stored below the diagonal of `lu`. `U` is upper triangular, and stored on and above it.
+/// Row `n` of `P·A` is row `permutation[n]` of `A`.
+/// Partial pivoting bounds the elements of `L` to a magnitude of one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Lu<E, const N:usize> {
+    pub lu: Matrix<E, N, N>,
+    pub permutation: [usize; N],
+}
+
+impl<E, const N:usize> Lu<E, N>
+    where E: Element
+{
+    /// Solve `A·x = b` for a vector, e.g. an array.
+    pub fn solve<A, B>( &self, b:&A ) -> Result<B, SolveError>
+        where A: AsRef<[E; N]>, B: From<[E; N]>
+    {
+        let b = b.as_ref();
+        let mut x = [E::zero(); N];
+        // Forward substitution of L·y = P·b.
+        for n in 0..N {
+            let mut sum = b[self.permutation[n]];
+            for (l, y) in self.lu.data[n].iter().zip( x.iter() ).take( n ) {
+                sum = mul_sub( sum, *l, *y )?;
+            }
+            x[n] = sum;
+        }
+        // Back substitution of U·x = y.
+        for n in (0..N).rev() {
+            let mut sum = x[n];
+            for (u, y) in self.lu.data[n].iter().zip( x.iter() ).skip( n+1 ) {
+                sum = mul_sub( sum, *u, *y )?;
+            }
+            x[n] = div( sum, self.lu.data[n][n] )?;
+        }
+        return Ok( B::from( x ) );
+    }
+    /// The determinant of `A`, or an overflow error.
+    pub fn determinant( &self ) -> Result<E, SolveError> {
+        let mut r_det = E::one();
+        for n in 0..N {
+            r_det = r_det.checked_mul( self.lu.data[n][n] ).ok_or( SolveError::Overflow )?;
+        }
+        // The sign of the permutation, as a cycle of length L takes L-1 swaps.
+        let mut visited = [false; N];
+        let mut cycles = 0;
+        for start in 0..N {
+            if visited[start]
+            {
+                continue;
+            }
+            cycles += 1;
+            let mut n = start;
+            while !visited[n] {
+                visited[n] = true;
+                n = self.permutation[n];
+            }
+        }
+        if !(N-cycles).is_multiple_of( 2 )
+        {
+            return E::zero().checked_sub( r_det ).ok_or( SolveError::Overflow );
+        }
+        return Ok( r_det );
+    }
+}
+
+/// The Cholesky factorization `A = L·Lᴴ` of a symmetric, or Hermitian, positive-definite matrix.
+///
+/// `l` is lower triangular with a positive real diagonal. Only the lower triangle of `A` is read.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cholesky<E, const N:usize> {
+    pub l: Matrix<E, N, N>,
+}
+
+impl<E, const N:usize> Cholesky<E, N>
+    where E: Element
+{
+    /// Solve `A·x = b` for a vector, e.g. an array.
+    pub fn solve<A, B>( &self, b:&A ) -> Result<B, SolveError>
+        where A: AsRef<[E; N]>, B: From<[E; N]>
+    {
+        let mut x = *b.as_ref();
+        // Forward substitution of L·y = b.
+        for n in 0..N {
+            let mut sum = x[n];
+            for (l, y) in self.l.data[n].iter().zip( x.iter() ).take( n ) {
+                sum = mul_sub( sum, *l, *y )?;
+            }
+            x[n] = div( sum, self.l.data[n][n] )?;
+        }
+        // Back substitution of Lᴴ·x = y.
+        for n in (0..N).rev() {
+            let mut sum = x[n];
+            for (row, y) in self.l.data.iter().zip( x.iter() ).skip( n+1 ) {
+                sum = mul_sub( sum, row[n].conj(), *y )?;
+            }
+            x[n] = div( sum, self.l.data[n][n] )?;
+        }
+        return Ok( B::from( x ) );
+    }
+}
+
+/// The QR factorization `A = Q·R` of an M×N matrix with M ≥ N, by Givens rotations.
+///
+/// `q` is unitary, and `r` is upper triangular with a real, non-negative diagonal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Qr<E, const M:usize, const N:usize> {
+    pub q: Matrix<E, M, M>,
+    pub r: Matrix<E, M, N>,
+}
+
+impl<E, const M:usize, const N:usize> Qr<E, M, N>
+    where E: Element
+{
+    /// The least-squares solution `x` which minimizes `|A·x - b|`, for a vector `b` of length M, e.g. an array.
+    ///
+    /// The exact solution of `A·x = b` for square matrices.
+    pub fn least_squares<A, B>( &self, b:&A ) -> Result<B, SolveError>
+        where A: AsRef<[E; M]>, B: From<[E; N]>
+    {
+        let b = b.as_ref();
+        let mut x = [E::zero(); N];
+        // y = Qᴴ·b, of which only the first N elements reach the solution.
+        for (n, item) in x.iter_mut().enumerate() {
+            let mut sum = E::zero();
+            for (row, value) in self.q.data.iter().zip( b.iter() ) {
+                sum = sum.checked_add( row[n].conj().checked_mul( *value ).ok_or( SolveError::Overflow )? ).ok_or( SolveError::Overflow )?;
+            }
+            *item = sum;
+        }
+        // Back substitution of R·x = y.
+        for n in (0..N).rev() {
+            let mut sum = x[n];
+            for (r, y) in self.r.data[n].iter().zip( x.iter() ).skip( n+1 ) {
+                sum = mul_sub( sum, *r, *y )?;
+            }
+            x[n] = div( sum, self.r.data[n][n] )?;
+        }
+        return Ok( B::from( x ) );
+    }
+}
+
+/// Rotate a pair of rows from column `start`, as `[x; y] ← [c s; -s* c]·[x; y]`.
+fn rotate<E>( x:&mut [E], y:&mut [E], c:E, s:E, start:usize ) -> Result<(), SolveError>
+    where E: Element
+{
+    for (a, b) in x.iter_mut().zip( y.iter_mut() ).skip( start ) {
+        let upper = c.checked_mul( *a ).ok_or( SolveError::Overflow )?
+            .checked_add( s.checked_mul( *b ).ok_or( SolveError::Overflow )? ).ok_or( SolveError::Overflow )?;
+        let lower = mul_sub( c.checked_mul( *b ).ok_or( SolveError::Overflow )?, s.conj(), *a )?;
+        *a = upper;
+        *b = lower;
+    }
+    return Ok(());
+}
+
+impl<E, const N:usize> Matrix<E, N, N>
+    where E: Element
+{
+    /// Return the LU factorization with partial pivoting, see `linalg::Lu`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use integer_array as ia;
+    /// use ia::trait_definitions::*;
+    /// use ia::linalg::SolveError;
+    /// use fixed::{types::extra::U20, FixedI32};
+    ///
+    /// ia::declare_array_real!( Arr3, 3, FixedI32<U20> );
+    /// ia::declare_matrix_real!( Mat3x3, 3, 3, FixedI32<U20> );
+    ///
+    /// let a = Mat3x3::new( [[0.0, 2.0, 1.0], [1.0, 1.0, 0.0], [2.0, 0.0, 3.0]].map( |row| row.map( FixedI32::<U20>::from_num ) ) );
+    /// let lu = a.lu().unwrap();
+    /// let x:Arr3 = lu.solve( &Arr3::new_from_f32_array( [3.0, 2.0, 5.0] ) ).unwrap();
+    /// assert_eq!{ x.as_array_f32(), [1.0, 1.0, 1.0] };
+    /// assert_eq!{ lu.determinant().unwrap(), -8 };
+    ///
+    /// // The second row is twice the first.
+    /// let b = Mat3x3::new( [[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 1.0]].map( |row| row.map( FixedI32::<U20>::from_num ) ) );
+    /// assert_eq!{ b.lu(), Err( SolveError::Singular ) };
+    /// ```
+    pub fn lu( &self ) -> Result<Lu<E, N>, SolveError> {
+        let scale = max_abs( self );
+        let mut lu = *self;
+        let mut permutation = [0; N];
+        for (n, item) in permutation.iter_mut().enumerate() {
+            *item = n;
+        }
+        for k in 0..N {
+            let mut pivot = k;
+            for n in k+1..N {
+                if lu.data[pivot][k].abs() < lu.data[n][k].abs()
+                {
+                    pivot = n;
+                }
+            }
+            lu.data.swap( k, pivot );
+            permutation.swap( k, pivot );
+            check_pivot( lu.data[k][k].abs(), scale )?;
+
+            let pivot_row = lu.data[k];
+            for row in lu.data.iter_mut().skip( k+1 ) {
+                let l = div( row[k], pivot_row[k] )?;
+                row[k] = l;
+                for (item, value) in row.iter_mut().zip( pivot_row.iter() ).skip( k+1 ) {
+                    *item = mul_sub( *item, l, *value )?;
+                }
+            }
+        }
+        return Ok( Lu { lu, permutation } );
+    }
+    /// Return the Cholesky factorization of a symmetric, or Hermitian, positive-definite matrix, see `linalg::Cholesky`.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use integer_array as ia;
+    /// use ia::trait_definitions::*;
+    /// use ia::linalg::SolveError;
+    /// use fixed::{types::extra::U20, FixedI32};
+    ///
+    /// ia::declare_array_real!( Arr2, 2, FixedI32<U20> );
+    /// ia::declare_matrix_real!( Mat2x2, 2, 2, FixedI32<U20> );
+    ///
+    /// let a = Mat2x2::new( [[4.0, 2.0], [2.0, 5.0]].map( |row| row.map( FixedI32::<U20>::from_num ) ) );
+    /// let cholesky = a.cholesky().unwrap();
+    /// assert_eq!{ cholesky.l.data, [[2, 0], [1, 2]] };
+    /// let x:Arr2 = cholesky.solve( &Arr2::new_from_f32_array( [6.0, 7.0] ) ).unwrap();
+    /// assert_eq!{ x.as_array_f32(), [1.0, 1.0] };
+    ///
+    /// let b = Mat2x2::new( [[1.0, 2.0], [2.0, 1.0]].map( |row| row.map( FixedI32::<U20>::from_num ) ) );
+    /// assert_eq!{ b.cholesky(), Err( SolveError::NotPositiveDefinite ) };
+    /// ```
+    pub fn cholesky( &self ) -> Result<Cholesky<E, N>, SolveError> {
+        let scale = max_abs( self );
+        let mut l = Matrix::<E, N, N>::zeros();
+        for k in 0..N {
+            let mut sum = self.data[k][k];
+            for item in l.data[k].iter().take( k ) {
+                sum = mul_sub( sum, *item, item.conj() )?;
+            }
+            if sum.re() <= 0
+            {
+                return Err( SolveError::NotPositiveDefinite );
+            }
+            check_pivot( sum.re(), scale )?;
+            let diagonal = E::from_real( sqrt( sum.re() ) );
+            l.data[k][k] = diagonal;
+
+            let pivot_row = l.data[k];
+            for n in k+1..N {
+                let mut sum = self.data[n][k];
+                for (item, value) in l.data[n].iter().zip( pivot_row.iter() ).take( k ) {
+                    sum = mul_sub( sum, *item, value.conj() )?;
+                }
+                l.data[n][k] = div( sum, diagonal )?;
+            }
+        }
+        return Ok( Cholesky { l } );
+    }
+    /// Solve `A·x = b` for a vector, e.g. an array, by LU factorization with partial pivoting.
+    pub fn solve<A, B>( &self, b:&A ) -> Result<B, SolveError>
+        where A: AsRef<[E; N]>, B: From<[E; N]>
+    {
+        return self.lu()?.solve( b );
+    }
+}
+
+impl<E, const M:usize, const N:usize> Matrix<E, M, N>
+    where E: Element
+{
+    /// Return the QR factorization by Givens rotations, see `linalg::Qr`. The matrix must have at least as many rows as columns.
+    ///
+    /// ## Example
+    ///
+    /// Fit a line `y = a + b·t` through four points in the least-squares sense.
+    ///
+    /// ```rust
+    /// use integer_array as ia;
+    /// use ia::trait_definitions::*;
+    /// use fixed::{types::extra::U20, FixedI32};
+    ///
+    /// ia::declare_array_real!( Arr2, 2, FixedI32<U20> );
+    /// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+    /// ia::declare_matrix_real!( Mat4x2, 4, 2, FixedI32<U20> );
+    ///
+    /// let a = Mat4x2::new( [[1.0, 0.0], [1.0, 1.0], [1.0, 2.0], [1.0, 3.0]].map( |row| row.map( FixedI32::<U20>::from_num ) ) );
+    /// let qr = a.qr().unwrap();
+    /// let x:Arr2 = qr.least_squares( &Arr4::new_from_f32_array( [1.0, 2.0, 2.0, 4.0] ) ).unwrap();
+    /// assert!{ (x.data[0].to_num::<f32>()-0.9).abs() < 0.001 };
+    /// assert!{ (x.data[1].to_num::<f32>()-0.9).abs() < 0.001 };
+    /// ```
+    pub fn qr( &self ) -> Result<Qr<E, M, N>, SolveError> {
+        assert!{ N <= M };
+        let scale = max_abs( self );
+        let mut r = *self;
+        // Qᴴ, as the product of the rotations.
+        let mut q_h = Matrix::<E, M, M>::identity();
+        for k in 0..N {
+            for n in k+1..M {
+                let (a, b) = (r.data[k][k], r.data[n][k]);
+                if b == E::zero()
+                {
+                    continue;
+                }
+                let magnitude = a.abs();
+                let norm = hypot( magnitude, b.abs() )?;
+                // c = |a|/r and s = (a/|a|)·b*/r, which rotate [a; b] onto [(a/|a|)·r; 0].
+                let c = E::from_real( magnitude ).checked_div( E::from_real( norm ) ).ok_or( SolveError::Overflow )?;
+                let phase = if magnitude == 0 { E::one() } else { div( a, E::from_real( magnitude ) )? };
+                let s = div( phase.checked_mul( b.conj() ).ok_or( SolveError::Overflow )?, E::from_real( norm ) )?;
+
+                let (upper, lower) = r.data.split_at_mut( n );
+                rotate( &mut upper[k], &mut lower[0], c, s, k )?;
+                let (upper, lower) = q_h.data.split_at_mut( n );
+                rotate( &mut upper[k], &mut lower[0], c, s, 0 )?;
+            }
+            // Turn the diagonal real and non-negative, so its elements are the pivot magnitudes.
+            let magnitude = r.data[k][k].abs();
+            if magnitude != 0
+            {
+                let phase = div( r.data[k][k].conj(), E::from_real( magnitude ) )?;
+                for item in r.data[k].iter_mut().skip( k ) {
+                    *item = item.checked_mul( phase ).ok_or( SolveError::Overflow )?;
+                }
+                for item in q_h.data[k].iter_mut() {
+                    *item = item.checked_mul( phase ).ok_or( SolveError::Overflow )?;
+                }
+                r.data[k][k] = E::from_real( magnitude );
+            }
+            check_pivot( magnitude, scale )?;
+        }
+        return Ok( Qr { q: q_h.hermitian(), r } );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use super::SolveError;
+    use fixed::{types::extra::U20, FixedI32};
+    use num::complex::Complex;
+
+    fn value( re:f64, im:f64 ) -> Complex<FixedI32<U20>> {
+        return Complex::new( FixedI32::<U20>::from_num(re), FixedI32::<U20>::from_num(im) );
+    }
+
+    #[test]
+    fn complex_factorizations_reproduce_the_matrix() {
+        integer_array::declare_array_complex!( CArr3, Arr3, 3, FixedI32<U20> );
+        integer_array::declare_matrix_complex!( CMat3x3, 3, 3, FixedI32<U20> );
+
+        let a = CMat3x3::new( [
+            [value( 2.0, 0.5 ), value( -1.0, 1.0 ), value( 0.5, 0.0 )],
+            [value( 0.0, 1.0 ), value( 3.0, 0.0 ), value( 1.0, -1.0 )],
+            [value( 1.0, 0.0 ), value( 0.5, 0.5 ), value( -2.0, 1.0 )],
+        ] );
+        let expected = CArr3 { data: [value( 1.0, 0.0 ), value( 0.0, -1.0 ), value( 0.5, 0.5 )] };
+        let b:CArr3 = a.mul_vector( &expected );
+        let error = |x:CArr3| x.data.iter().zip( expected.data.iter() ).map( |(x, e)| (x.re-e.re).abs().max( (x.im-e.im).abs() ) ).max().unwrap();
+
+        let x:CArr3 = a.solve( &b ).unwrap();
+        assert!{ error( x ) < 0.0001 };
+        let qr = a.qr().unwrap();
+        let x:CArr3 = qr.least_squares( &b ).unwrap();
+        assert!{ error( x ) < 0.0001 };
+        let difference = a-qr.q*qr.r;
+        for item in difference.data.iter().flatten() {
+            assert!{ item.re.abs() < 0.0001 && item.im.abs() < 0.0001 };
+        }
+
+        // A·Aᴴ is Hermitian and positive definite.
+        let gram = a*a.hermitian();
+        let b:CArr3 = gram.mul_vector( &expected );
+        let cholesky = gram.cholesky().unwrap();
+        let x:CArr3 = cholesky.solve( &b ).unwrap();
+        assert!{ error( x ) < 0.001 };
+        let difference = gram-cholesky.l*cholesky.l.hermitian();
+        for item in difference.data.iter().flatten() {
+            assert!{ item.re.abs() < 0.0001 && item.im.abs() < 0.0001 };
+        }
+    }
+
+    #[test]
+    fn poor_inputs_are_reported() {
+        integer_array::declare_array_real!( Arr2, 2, FixedI32<U20> );
+        integer_array::declare_matrix_real!( Mat2x2, 2, 2, FixedI32<U20> );
+        let matrix = |data:[[f64; 2]; 2]| Mat2x2::new( data.map( |row| row.map( FixedI32::<U20>::from_num ) ) );
+
+        assert_eq!{ Mat2x2::zeros().lu(), Err( SolveError::Singular ) };
+        assert_eq!{ matrix( [[1.0, 1.0], [1.0, 1.0]] ).qr(), Err( SolveError::Singular ) };
+        // A pivot of 1/2048 is below 2^-10 of the largest element.
+        let nearly_singular = matrix( [[1.0, 1.0], [1.0, 1.0+1.0/2048.0]] );
+        assert_eq!{ nearly_singular.lu(), Err( SolveError::IllConditioned ) };
+        assert_eq!{ nearly_singular.cholesky(), Err( SolveError::IllConditioned ) };
+        assert_eq!{ nearly_singular.qr(), Err( SolveError::IllConditioned ) };
+        assert!{ matrix( [[1.0, 1.0], [1.0, 1.0+1.0/512.0]] ).lu().is_ok() };
+        assert_eq!{ matrix( [[-1.0, 0.0], [0.0, 1.0]] ).cholesky(), Err( SolveError::NotPositiveDefinite ) };
+
+        // A solution outside the range of the type is an error, not saturated.
+        let small = matrix( [[1.0/256.0, 0.0], [0.0, 1.0]] );
+        let result:Result<Arr2, SolveError> = small.solve( &Arr2::new_from_f32_array( [1000.0, 0.0] ) );
+        assert_eq!{ result, Err( SolveError::Overflow ) };
+    }
+}
@@ -0,0 +1,539 @@
+//! Fixed-size matrices of real or complex fixed-point elements.
+//!
+//! `Matrix<E, R, C>` is an R×C matrix, stored row by row.
+//! The dimensions are checked at compile time, so a product or a transpose has its own type.
+//! Named matrix types are declared with `declare_matrix_real` and `declare_matrix_complex`.
+//!
+//! Matrices multiply against the real and complex array types, which convert from and to primitive arrays.
+//! Products are accumulated in the element type.
+
+use fixed::types::extra::{LeEqU8, LeEqU16, LeEqU32, LeEqU64, LeEqU128};
+use core::convert::TryFrom;
+use fixed::traits::FixedSigned;
+use fixed::{FixedI8, FixedI16, FixedI32, FixedI64, FixedI128};
+use num::complex::Complex;
+use crate::utility::{wide_bits, from_wide_bits};
+
+/// The arithmetic of matrix elements, implemented for the signed fixed types and complex numbers of them.
+pub trait Element: Copy + PartialEq + core::fmt::Debug {
+    /// The real fixed type of the element, or of its parts.
+    type Real: FixedSigned;
+    /// The additive identity.
+    fn zero() -> Self;
+    /// The multiplicative identity.
+    fn one() -> Self;
+    /// The sum of two elements.
+    fn add( self, other:Self ) -> Self;
+    /// The difference of two elements.
+    fn sub( self, other:Self ) -> Self;
+    /// The product of two elements.
+    fn mul( self, other:Self ) -> Self;
+    /// The complex conjugate, which leaves real elements as they are.
+    fn conj( self ) -> Self;
+    /// The sum of two elements, or `None` on overflow.
+    fn checked_add( self, other:Self ) -> Option<Self>;
+    /// The difference of two elements, or `None` on overflow.
+    fn checked_sub( self, other:Self ) -> Option<Self>;
+    /// The product of two elements, or `None` on overflow.
+    fn checked_mul( self, other:Self ) -> Option<Self>;
+    /// The quotient of two elements, or `None` on overflow or division by zero.
+    fn checked_div( self, other:Self ) -> Option<Self>;
+    /// The real part, which is the element itself for real elements.
+    fn re( self ) -> Self::Real;
+    /// The magnitude, calculated in wide integers so it does not overflow before the result does.
+    fn abs( self ) -> Self::Real;
+    /// The sign, `-1`, `0` or `1`, which is taken of the real and imaginary parts separately for complex elements.
+    fn signum( self ) -> Self;
+    /// The element of a real value.
+    fn from_real( value:Self::Real ) -> Self;
+    /// The product with a real value.
+    fn scale( self, value:Self::Real ) -> Self;
+}
+
+/// The magnitude of a complex number of wide bits, `sqrt(re²+im²)`, with saturation.
+///
+/// Parts of more than 63 bits are scaled down before they are squared, so the sum of the squares fits.
+pub(crate) fn wide_magnitude( re:i128, im:i128 ) -> i128 {
+    let (re, im) = (re.unsigned_abs(), im.unsigned_abs());
+    let shift = (128-re.max( im ).leading_zeros()).saturating_sub(63);
+    let (re, im) = (re >> shift, im >> shift);
+    let root = (re*re+im*im).isqrt();
+    if root.leading_zeros() <= shift
+    {
+        return i128::MAX;
+    }
+    return (root << shift) as i128;
+}
+
+/// The quotient `(numerator·2^shift)/denominator`, rounded towards zero, or `None` if it does not fit.
+///
+/// The shift is done bit by bit as in long division, so it never overflows before the quotient does.
+fn wide_div_shift( numerator:i128, shift:u32, denominator:i128 ) -> Option<i128> {
+    if denominator == 0
+    {
+        return None;
+    }
+    let (numerator_abs, denominator_abs) = (numerator.unsigned_abs(), denominator.unsigned_abs());
+    let mut quotient = numerator_abs/denominator_abs;
+    let mut remainder = numerator_abs%denominator_abs;
+    for _ in 0..shift {
+        quotient = quotient.checked_mul(2)?;
+        // The remainder is below the denominator, so it can be doubled as long as the denominator fits in 127 bits.
+        remainder <<= 1;
+        if denominator_abs <= remainder
+        {
+            quotient += 1;
+            remainder -= denominator_abs;
+        }
+    }
+    let quotient = i128::try_from( quotient ).ok()?;
+    return Some( if (numerator < 0) != (denominator < 0) { -quotient } else { quotient } );
+}
+
+macro_rules! impl_element_real{
+    ( $Fixed:ident, $LeEq:ident ) => {
+        impl<Frac: $LeEq> Element for $Fixed<Frac> {
+            type Real = Self;
+            fn zero() -> Self {
+                return Self::from_num(0);
+            }
+            fn one() -> Self {
+                return Self::from_num(1);
+            }
+            fn add( self, other:Self ) -> Self {
+                return self+other;
+            }
+            fn sub( self, other:Self ) -> Self {
+                return self-other;
+            }
+            fn mul( self, other:Self ) -> Self {
+                return self*other;
+            }
+            fn conj( self ) -> Self {
+                return self;
+            }
+            fn checked_add( self, other:Self ) -> Option<Self> {
+                return $Fixed::checked_add( self, other );
+            }
+            fn checked_sub( self, other:Self ) -> Option<Self> {
+                return $Fixed::checked_sub( self, other );
+            }
+            fn checked_mul( self, other:Self ) -> Option<Self> {
+                return $Fixed::checked_mul( self, other );
+            }
+            fn checked_div( self, other:Self ) -> Option<Self> {
+                return $Fixed::checked_div( self, other );
+            }
+            fn re( self ) -> Self {
+                return self;
+            }
+            fn abs( self ) -> Self {
+                return self.saturating_abs();
+            }
+            fn signum( self ) -> Self {
+                return $Fixed::signum( self );
+            }
+            fn from_real( value:Self ) -> Self {
+                return value;
+            }
+            fn scale( self, value:Self ) -> Self {
+                return self*value;
+            }
+        }
+    }
+}
+
+impl_element_real!( FixedI8, LeEqU8 );
+impl_element_real!( FixedI16, LeEqU16 );
+impl_element_real!( FixedI32, LeEqU32 );
+impl_element_real!( FixedI64, LeEqU64 );
+impl_element_real!( FixedI128, LeEqU128 );
+
+impl<T> Element for Complex<T>
+    where T: FixedSigned
+{
+    type Real = T;
+    fn zero() -> Self {
+        return Complex::new( T::from_num(0), T::from_num(0) );
+    }
+    fn one() -> Self {
+        return Complex::new( T::from_num(1), T::from_num(0) );
+    }
+    fn add( self, other:Self ) -> Self {
+        return Complex::new( self.re+other.re, self.im+other.im );
+    }
+    fn sub( self, other:Self ) -> Self {
+        return Complex::new( self.re-other.re, self.im-other.im );
+    }
+    fn mul( self, other:Self ) -> Self {
+        return crate::fft::complex_mul( self, other );
+    }
+    fn conj( self ) -> Self {
+        return crate::fft::complex_conj( self );
+    }
+    fn checked_add( self, other:Self ) -> Option<Self> {
+        return Some( Complex::new( self.re.checked_add( other.re )?, self.im.checked_add( other.im )? ) );
+    }
+    fn checked_sub( self, other:Self ) -> Option<Self> {
+        return Some( Complex::new( self.re.checked_sub( other.re )?, self.im.checked_sub( other.im )? ) );
+    }
+    fn checked_mul( self, other:Self ) -> Option<Self> {
+        let re = self.re.checked_mul( other.re )?.checked_sub( self.im.checked_mul( other.im )? )?;
+        let im = self.re.checked_mul( other.im )?.checked_add( self.im.checked_mul( other.re )? )?;
+        return Some( Complex::new( re, im ) );
+    }
+    fn checked_div( self, other:Self ) -> Option<Self> {
+        // a/b = a·conj(b)/|b|². The parts are scaled down to 62 bits, so the products and their sums fit,
+        // and the quotient is scaled back up with the fractional bits of the type by long division.
+        let scale_down = |re:i128, im:i128| -> (i128, i128, u32) {
+            let shift = (128-re.unsigned_abs().max( im.unsigned_abs() ).leading_zeros()).saturating_sub(62);
+            return ( re >> shift, im >> shift, shift );
+        };
+        let (a_re, a_im, a_shift) = scale_down( wide_bits(self.re), wide_bits(self.im) );
+        let (b_re, b_im, b_shift) = scale_down( wide_bits(other.re), wide_bits(other.im) );
+        let power = b_re*b_re+b_im*b_im;
+        if power == 0
+        {
+            return None;
+        }
+        // With the scaling, a/b = a'·conj(b')/|b'|²·2^(a_shift-b_shift).
+        let shift = T::FRAC_NBITS as i32+a_shift as i32-b_shift as i32;
+        let quotient = |numerator:i128| -> Option<i128> {
+            return match shift {
+                0.. => wide_div_shift( numerator, shift as u32, power ),
+                _   => Some( (numerator/power) >> -shift ),
+            };
+        };
+        let re = quotient( a_re*b_re+a_im*b_im )?;
+        let im = quotient( a_im*b_re-a_re*b_im )?;
+        if re < wide_bits(T::MIN) || wide_bits(T::MAX) < re || im < wide_bits(T::MIN) || wide_bits(T::MAX) < im
+        {
+            return None;
+        }
+        return Some( Complex::new( from_wide_bits(re), from_wide_bits(im) ) );
+    }
+    fn re( self ) -> T {
+        return self.re;
+    }
+    fn abs( self ) -> T {
+        return from_wide_bits( wide_magnitude( wide_bits(self.re), wide_bits(self.im) ) );
+    }
+    fn signum( self ) -> Self {
+        return Complex::new( self.re.signum(), self.im.signum() );
+    }
+    fn from_real( value:T ) -> Self {
+        return Complex::new( value, T::from_num(0) );
+    }
+    fn scale( self, value:T ) -> Self {
+        return Complex::new( self.re*value, self.im*value );
+    }
+}
+
+/// An R×C matrix, stored row by row.
+///
+/// # Example
+///
+/// ```
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr2, 2, FixedI32<U20> );
+/// ia::declare_array_real!( Arr3, 3, FixedI32<U20> );
+/// ia::declare_matrix_real!( Mat2x3, 2, 3, FixedI32<U20> );
+///
+/// let a = Mat2x3::new( [ Arr3::new_from_f32_array( [1.0, 2.0, 3.0] ).data,
+///                        Arr3::new_from_f32_array( [4.0, 5.0, 6.0] ).data ] );
+///
+/// // Matrix-vector and matrix-matrix products.
+/// let y:Arr2 = a.mul_vector( &Arr3::new_from_f32_array( [1.0, 0.0, -1.0] ) );
+/// assert_eq!{ y.as_array_i32(), [-2, -2] };
+/// let gram = a.mul_matrix( &a.transpose() );
+/// assert_eq!{ gram[(0, 1)], 32 };
+///
+/// // Rows and columns are extracted into arrays.
+/// let row:Arr3 = a.row( 1 );
+/// let column:Arr2 = a.column( 2 );
+/// assert_eq!{ row.as_array_i32(), [4, 5, 6] };
+/// assert_eq!{ column.as_array_i32(), [3, 6] };
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix<E, const R:usize, const C:usize> {
+    pub data: [[E; C]; R],
+}
+
+impl<E, const R:usize, const C:usize> Matrix<E, R, C>
+    where E: Element
+{
+    /// Generate a matrix from its rows.
+    pub fn new( data:[[E; C]; R] ) -> Self {
+        return Matrix { data };
+    }
+    /// Generate a matrix of zeros.
+    pub fn zeros() -> Self {
+        return Matrix { data: [[E::zero(); C]; R] };
+    }
+    /// Generate a matrix with ones on the main diagonal, and zeros elsewhere.
+    pub fn identity() -> Self {
+        let mut r_matrix = Self::zeros();
+        for n in 0..R.min(C) {
+            r_matrix.data[n][n] = E::one();
+        }
+        return r_matrix;
+    }
+    /// The number of rows.
+    pub fn rows( &self ) -> usize {
+        return R;
+    }
+    /// The number of columns.
+    pub fn columns( &self ) -> usize {
+        return C;
+    }
+    /// Return the transpose.
+    pub fn transpose( &self ) -> Matrix<E, C, R> {
+        let mut r_matrix = Matrix::<E, C, R>::zeros();
+        for r in 0..R {
+            for c in 0..C {
+                r_matrix.data[c][r] = self.data[r][c];
+            }
+        }
+        return r_matrix;
+    }
+    /// Return the Hermitian (conjugate) transpose. Equal to the transpose for real matrices.
+    pub fn hermitian( &self ) -> Matrix<E, C, R> {
+        let mut r_matrix = Matrix::<E, C, R>::zeros();
+        for r in 0..R {
+            for c in 0..C {
+                r_matrix.data[c][r] = self.data[r][c].conj();
+            }
+        }
+        return r_matrix;
+    }
+    /// Return the product with a C×K matrix.
+    pub fn mul_matrix<const K:usize>( &self, other:&Matrix<E, C, K> ) -> Matrix<E, R, K> {
+        let mut r_matrix = Matrix::<E, R, K>::zeros();
+        for r in 0..R {
+            for k in 0..K {
+                let mut sum = E::zero();
+                for c in 0..C {
+                    sum = sum.add( self.data[r][c].mul( other.data[c][k] ) );
+                }
+                r_matrix.data[r][k] = sum;
+            }
+        }
+        return r_matrix;
+    }
+    /// Return the product with a vector of length C, e.g. an array, as a vector of length R.
+    pub fn mul_vector<A, B>( &self, vector:&A ) -> B
+        where A: AsRef<[E; C]>, B: From<[E; R]>
+    {
+        let vector = vector.as_ref();
+        let mut r_array = [E::zero(); R];
+        for (item, row) in r_array.iter_mut().zip( self.data.iter() ) {
+            for (element, value) in row.iter().zip( vector.iter() ) {
+                *item = item.add( element.mul( *value ) );
+            }
+        }
+        return B::from( r_array );
+    }
+    /// Return a row, e.g. as an array.
+    pub fn row<A>( &self, index:usize ) -> A
+        where A: From<[E; C]>
+    {
+        return A::from( self.data[index] );
+    }
+    /// Return a column, e.g. as an array.
+    pub fn column<A>( &self, index:usize ) -> A
+        where A: From<[E; R]>
+    {
+        let mut r_array = [E::zero(); R];
+        for (r, item) in r_array.iter_mut().enumerate() {
+            *item = self.data[r][index];
+        }
+        return A::from( r_array );
+    }
+    /// Return the matrix with each element multiplied by a value.
+    pub fn scale( &self, value:E ) -> Self {
+        let mut r_matrix = *self;
+        for row in r_matrix.data.iter_mut() {
+            for item in row.iter_mut() {
+                *item = item.mul( value );
+            }
+        }
+        return r_matrix;
+    }
+}
+
+impl<E, const R:usize, const C:usize> core::ops::Index<(usize, usize)> for Matrix<E, R, C> {
+    type Output = E;
+    /// Returns the element at (row, column).
+    #[inline]
+    fn index( &self, index:(usize, usize) ) -> &E {
+        return &self.data[index.0][index.1];
+    }
+}
+
+impl<E, const R:usize, const C:usize> core::ops::IndexMut<(usize, usize)> for Matrix<E, R, C> {
+    /// Returns a mutable reference to the element at (row, column).
+    #[inline]
+    fn index_mut( &mut self, index:(usize, usize) ) -> &mut E {
+        return &mut self.data[index.0][index.1];
+    }
+}
+
+impl<E, const R:usize, const C:usize> core::ops::Add for Matrix<E, R, C>
+    where E: Element
+{
+    type Output = Self;
+    fn add( self, other:Self ) -> Self {
+        let mut r_matrix = self;
+        for r in 0..R {
+            for c in 0..C {
+                r_matrix.data[r][c] = Element::add( self.data[r][c], other.data[r][c] );
+            }
+        }
+        return r_matrix;
+    }
+}
+
+impl<E, const R:usize, const C:usize> core::ops::Sub for Matrix<E, R, C>
+    where E: Element
+{
+    type Output = Self;
+    fn sub( self, other:Self ) -> Self {
+        let mut r_matrix = self;
+        for r in 0..R {
+            for c in 0..C {
+                r_matrix.data[r][c] = Element::sub( self.data[r][c], other.data[r][c] );
+            }
+        }
+        return r_matrix;
+    }
+}
+
+impl<E, const R:usize, const C:usize, const K:usize> core::ops::Mul<Matrix<E, C, K>> for Matrix<E, R, C>
+    where E: Element
+{
+    type Output = Matrix<E, R, K>;
+    fn mul( self, other:Matrix<E, C, K> ) -> Matrix<E, R, K> {
+        return self.mul_matrix( &other );
+    }
+}
+
+/// This macro declares an R×C matrix type of real fixed-point elements, see `matrix::Matrix`.
+///
+/// ## Arguments
+/// * `name` - The name of the matrix type. E.g. Mat3x3.
+/// * `R`    - The number of rows.
+/// * `C`    - The number of columns.
+/// * `T`    - The fixed type of the elements.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_matrix_real!( Mat2x2, 2, 2, FixedI32<U20> );
+/// let mut a = Mat2x2::identity();
+/// a[(0, 1)] = FixedI32::<U20>::from_num(2);
+/// let b = a*a;
+/// assert_eq!{ b[(0, 1)], 4 };
+/// assert_eq!{ b.transpose()[(1, 0)], 4 };
+/// ```
+#[macro_export]
+macro_rules! declare_matrix_real{
+    ( $name:ident, $R:expr, $C:expr, $T:ty ) => {
+        /// Matrix of real fixed-point elements.
+        #[allow(dead_code)]
+        pub type $name = integer_array::matrix::Matrix<$T, $R, $C>;
+    }
+}
+
+/// This macro declares an R×C matrix type of complex fixed-point elements, see `matrix::Matrix`.
+///
+/// ## Arguments
+/// * `name` - The name of the matrix type. E.g. CMat4x4.
+/// * `R`    - The number of rows.
+/// * `C`    - The number of columns.
+/// * `T`    - The fixed type of the real and imaginary parts.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex as C;
+///
+/// ia::declare_array_complex!( CArr2, Arr2, 2, FixedI32<U20> );
+/// ia::declare_matrix_complex!( CMat2x2, 2, 2, FixedI32<U20> );
+///
+/// let j = C::new( FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(1) );
+/// let mut a = CMat2x2::identity();
+/// a[(0, 1)] = j;
+/// assert_eq!{ a.hermitian()[(1, 0)].im, -1 };
+///
+/// // [1 j; 0 1]·[1; 1] = [1+j; 1]
+/// let y:CArr2 = a.mul_vector( &CArr2::new_from_i32( 1, 0 ) );
+/// assert_eq!{ y.as_array_f32(), [C{re:1.0, im:1.0}, C{re:1.0, im:0.0}] };
+/// ```
+#[macro_export]
+macro_rules! declare_matrix_complex{
+    ( $name:ident, $R:expr, $C:expr, $T:ty ) => {
+        /// Matrix of complex fixed-point elements.
+        #[allow(dead_code)]
+        pub type $name = integer_array::matrix::Matrix<num::complex::Complex<$T>, $R, $C>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+    use num::complex::Complex;
+
+    #[test]
+    fn complex_products_match_their_definition() {
+        integer_array::declare_matrix_complex!( CMat2x3, 2, 3, FixedI32<U20> );
+        let value = |re:f64, im:f64| Complex::new( FixedI32::<U20>::from_num(re), FixedI32::<U20>::from_num(im) );
+        let mut a = CMat2x3::zeros();
+        for r in 0..2 {
+            for c in 0..3 {
+                a[(r, c)] = value( (r+c) as f64*0.5, r as f64-c as f64 );
+            }
+        }
+        // The Gram matrix A·A^H is Hermitian, with the squared row norms on its diagonal.
+        let gram = a*a.hermitian();
+        for r in 0..2 {
+            let norm:f64 = (0..3).map( |c| a[(r, c)].re.to_num::<f64>().powi(2)+a[(r, c)].im.to_num::<f64>().powi(2) ).sum();
+            assert_eq!{ gram[(r, r)], value( norm, 0.0 ) };
+        }
+        assert_eq!{ gram[(0, 1)], Complex::new( gram[(1, 0)].re, -gram[(1, 0)].im ) };
+        assert_eq!{ (a-a+a).hermitian().hermitian(), a };
+    }
+
+    #[test]
+    fn wide_complex_elements_do_not_overflow() {
+        use crate::matrix::Element;
+        use fixed::types::I32F32;
+
+        let corner = Complex::new( I32F32::MIN, I32F32::MIN );
+        assert_eq!{ Element::abs( corner ), I32F32::MAX };
+        let value = Complex::new( I32F32::from_num(-1.2e9), I32F32::from_num(1.6e9) );
+        assert_eq!{ Element::abs( value ), 2.0e9 };
+
+        // (-1.2+1.6i)·10^9/(0.6-0.8i)·10^9 = -2
+        let divisor = Complex::new( I32F32::from_num(0.6e9), I32F32::from_num(-0.8e9) );
+        assert_eq!{ Element::checked_div( value, divisor ), Some( Complex::new( I32F32::from_num(-2), I32F32::from_num(0) ) ) };
+        assert_eq!{ Element::checked_div( corner, Complex::new( I32F32::DELTA, I32F32::from_num(0) ) ), None };
+        let quotient = Element::checked_div( Complex::new( I32F32::from_num(1), I32F32::from_num(0) ), value ).unwrap();
+        assert!{ (quotient.re.to_num::<f64>()+0.3e-9).abs() < 1e-9 && (quotient.im.to_num::<f64>()+0.4e-9).abs() < 1e-9 };
+
+        // The 32-bit quotients are unchanged by the scaling.
+        let a = Complex::new( FixedI32::<U20>::from_num(1), FixedI32::<U20>::from_num(2) );
+        let b = Complex::new( FixedI32::<U20>::from_num(3), FixedI32::<U20>::from_num(-1) );
+        let quotient = Element::checked_div( a, b ).unwrap();
+        assert!{ (quotient.re-FixedI32::<U20>::from_num(0.1)).abs() <= FixedI32::<U20>::DELTA && (quotient.im-FixedI32::<U20>::from_num(0.7)).abs() <= FixedI32::<U20>::DELTA };
+    }
+}
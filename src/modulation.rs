@@ -0,0 +1,369 @@
+use fixed::traits::FixedSigned;
+use num::complex::Complex;
+use crate::utility;
+
+/// Gray-coded constellations.
+///
+/// The bits of a symbol are counted MSB first.
+/// For BPSK, QPSK and the square QAM constellations, the first half of the bits select the real (I) level and the second half the imaginary (Q) level.
+/// Each axis is a Gray-coded PAM where a leading zero bit gives a positive level, e.g. `00→3, 01→1, 11→-1, 10→-3` for 16-QAM.
+/// 8-PSK places the Gray-decoded symbol `k` at the angle `kπ/4`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Constellation {
+    Bpsk,
+    Qpsk,
+    Psk8,
+    Qam16,
+    Qam64,
+}
+
+impl Constellation {
+    /// Returns the number of bits carried by each symbol.
+    pub fn bits_per_symbol( &self ) -> usize {
+        match self {
+            Constellation::Bpsk  => 1,
+            Constellation::Qpsk  => 2,
+            Constellation::Psk8  => 3,
+            Constellation::Qam16 => 4,
+            Constellation::Qam64 => 6,
+        }
+    }
+    /// Returns the number of levels along each axis of a square constellation. BPSK and 8-PSK return zero.
+    pub fn levels_per_axis( &self ) -> usize {
+        match self {
+            Constellation::Qpsk  => 2,
+            Constellation::Qam16 => 4,
+            Constellation::Qam64 => 8,
+            _ => 0,
+        }
+    }
+}
+
+/// Convert a Gray-coded value to its binary index.
+pub fn gray_decode( gray:u8 ) -> u8 {
+    let mut index = gray;
+    let mut shift = gray >> 1;
+    while shift != 0
+    {
+        index ^= shift;
+        shift >>= 1;
+    }
+    return index;
+}
+
+/// Returns the scaling which brings the integer-grid constellation to unit average power.
+///
+/// The integer grid has levels at the odd integers for the square constellations, and unit magnitude for BPSK and 8-PSK.
+pub fn unit_power_scale<T>( constellation:Constellation ) -> T
+    where T: FixedSigned
+{
+    match constellation {
+        Constellation::Bpsk  => T::from_num(1),
+        Constellation::Qpsk  => T::from_num(fixed::consts::FRAC_1_SQRT_2),
+        Constellation::Psk8  => T::from_num(1),
+        // 1/sqrt(10) and 1/sqrt(42).
+        Constellation::Qam16 => T::from_num(0.316_227_766_016_837_9f64),
+        Constellation::Qam64 => T::from_num(0.154_303_349_962_091_9f64),
+    }
+}
+
+/// The signed PAM level `L-1-2k` of the Gray-coded axis bits, on the odd integer grid.
+fn pam_level( bits:u8, levels:usize ) -> i32 {
+    return levels as i32-1-2*gray_decode(bits) as i32;
+}
+
+/// Map a symbol to its Gray-coded constellation point.
+///
+/// ## Arguments
+/// * `symbol`        - The bits of the symbol, MSB first. Only the `bits_per_symbol()` least significant bits are used.
+/// * `constellation` - The constellation.
+/// * `unit_power`    - Scale the constellation to unit average power. Otherwise, the square constellations are on the odd integer grid.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::modulation::{self, Constellation};
+///
+/// let point = modulation::map_symbol::<FixedI32<U20>>( 0b1101, Constellation::Qam16, false );
+/// assert_eq!{ point.re, -1 };
+/// assert_eq!{ point.im, 1 };
+/// ```
+pub fn map_symbol<T>( symbol:u8, constellation:Constellation, unit_power:bool ) -> Complex<T>
+    where T: FixedSigned
+{
+    let bits   = constellation.bits_per_symbol();
+    let symbol = symbol & ((1u8 << bits)-1);
+
+    let point = match constellation {
+        Constellation::Bpsk => Complex::new( T::from_num(pam_level(symbol, 2)), T::from_num(0) ),
+        Constellation::Psk8 => {
+            let angle = T::from_num(fixed::consts::FRAC_PI_4)*T::from_num(gray_decode(symbol));
+            Complex::new( utility::cos_precise_fixed(angle), utility::sin_precise_fixed(angle) )
+        },
+        _ => {
+            let levels = constellation.levels_per_axis();
+            let half   = bits/2;
+            Complex::new( T::from_num(pam_level(symbol >> half, levels)),
+                          T::from_num(pam_level(symbol & ((1u8 << half)-1), levels)) )
+        },
+    };
+
+    if unit_power
+    {
+        let scale = unit_power_scale::<T>( constellation );
+        return Complex::new( point.re*scale, point.im*scale );
+    }
+    return point;
+}
+
+/// Pack the bits of a symbol, MSB first, from a buffer with one bit per item.
+///
+/// Non-zero items are ones. Missing bits at the end of the buffer are zeros.
+pub fn pack_bits( bits:&[u8], bits_per_symbol:usize, index:usize ) -> u8 {
+    let mut symbol = 0u8;
+    for k in 0..bits_per_symbol {
+        let bit = bits.get( index*bits_per_symbol+k ).map_or( 0, |bit| (*bit != 0) as u8 );
+        symbol = (symbol << 1) | bit;
+    }
+    return symbol;
+}
+
+/// Unpack the bits of a symbol, MSB first, into a buffer with one bit per item.
+///
+/// Bits which do not fit in the buffer are dropped.
+pub fn unpack_bits( symbol:u8, bits_per_symbol:usize, index:usize, bits:&mut [u8] ) {
+    for k in 0..bits_per_symbol {
+        if let Some(bit) = bits.get_mut( index*bits_per_symbol+k )
+        {
+            *bit = (symbol >> (bits_per_symbol-1-k)) & 1;
+        }
+    }
+}
+
+/// The scaling of the constellation grid.
+fn grid_scale<T>( constellation:Constellation, unit_power:bool ) -> T
+    where T: FixedSigned
+{
+    if unit_power
+    {
+        return unit_power_scale( constellation );
+    }
+    return T::from_num(1);
+}
+
+/// The Gray-coded bits of the nearest level of a PAM axis.
+fn pam_decision<T>( value:T, levels:usize, scale:T ) -> u8
+    where T: FixedSigned
+{
+    // Points beyond the outer levels decide as the outer levels, and are clamped so the quotient stays in range.
+    let span  = scale*T::from_num(levels-1);
+    let value = value.clamp( -span, span );
+    let index = ( (T::from_num(levels-1)-value/scale)/T::from_num(2)+T::from_num(0.5f32) ).floor();
+    let index = index.to_num::<i32>().clamp( 0, levels as i32-1 ) as u8;
+    return index ^ (index >> 1);
+}
+
+/// Max-log LLRs of the Gray-coded bits of a PAM axis.
+///
+/// The difference of the squared distances to the nearest levels `a` with a one and `b` with a zero is `(b-a)(2y-a-b)`,
+/// which is calculated in wide integers without forming the squares.
+fn pam_llrs<T>( value:T, levels:usize, scale:T, noise_variance:T, llrs:&mut [T] )
+    where T: FixedSigned
+{
+    let bits = levels.trailing_zeros() as usize;
+    let (value, noise_variance) = (utility::wide_bits( value ), utility::wide_bits( noise_variance ));
+    for (j, llr) in llrs.iter_mut().take( bits ).enumerate() {
+        // The distances to the nearest levels with a zero and with a one, and the levels.
+        let mut zero = (i128::MAX, 0i128);
+        let mut one  = (i128::MAX, 0i128);
+        for index in 0..levels {
+            let gray     = index ^ (index >> 1);
+            let level    = utility::wide_bits( scale*T::from_num( pam_level(gray as u8, levels) ) );
+            let distance = (value-level).abs();
+            let nearest  = if (gray >> (bits-1-j)) & 1 == 0 { &mut zero } else { &mut one };
+            if distance < nearest.0
+            {
+                *nearest = (distance, level);
+            }
+        }
+        let (a, b) = (one.1, zero.1);
+        *llr = utility::from_wide_bits( (b-a).saturating_mul( 2*value-a-b )/noise_variance );
+    }
+}
+
+/// Hard decision of a received point to the nearest Gray-coded symbol.
+///
+/// ## Arguments
+/// * `point`         - The received point.
+/// * `constellation` - The constellation.
+/// * `unit_power`    - The constellation was scaled to unit average power.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::modulation::{self, Constellation};
+///
+/// let point = Complex::new( FixedI32::<U20>::from_num(-0.8), FixedI32::<U20>::from_num(2.6) );
+/// assert_eq!{ modulation::demap_symbol( point, Constellation::Qam16, false ), 0b1100 };
+/// ```
+pub fn demap_symbol<T>( point:Complex<T>, constellation:Constellation, unit_power:bool ) -> u8
+    where T: FixedSigned
+{
+    let scale = grid_scale::<T>( constellation, unit_power );
+    match constellation {
+        Constellation::Bpsk => pam_decision( point.re, 2, scale ),
+        Constellation::Psk8 => {
+            let sector = utility::atan2_fixed( point.im, point.re )/T::from_num(fixed::consts::FRAC_PI_4);
+            let index  = ( (sector+T::from_num(0.5f32)).floor().to_num::<i32>() & 7 ) as u8;
+            index ^ (index >> 1)
+        },
+        _ => {
+            let levels = constellation.levels_per_axis();
+            let half   = constellation.bits_per_symbol()/2;
+            (pam_decision( point.re, levels, scale ) << half) | pam_decision( point.im, levels, scale )
+        },
+    }
+}
+
+/// Max-log log-likelihood ratios of the bits of a received point, `log(P(b=0)/P(b=1))`.
+///
+/// `LLR(b) = (min|y-s|^2 over s with b=1 - min|y-s|^2 over s with b=0)/σ^2`
+///
+/// A positive LLR favours a zero bit. The BPSK, QPSK and square QAM constellations are demapped per axis.
+/// The distances are compared in wide integers, and only the LLRs saturate, so they keep their sign for points far outside the constellation.
+///
+/// ## Arguments
+/// * `point`          - The received point.
+/// * `constellation`  - The constellation.
+/// * `unit_power`     - The constellation was scaled to unit average power.
+/// * `noise_variance` - The variance σ^2 of the complex noise.
+/// * `llrs`           - The `bits_per_symbol()` LLRs of the symbol, MSB first.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::modulation::{self, Constellation};
+///
+/// let point = Complex::new( FixedI32::<U20>::from_num(0.5), FixedI32::<U20>::from_num(0) );
+/// let mut llrs = [FixedI32::<U20>::from_num(0); 1];
+/// modulation::demap_llrs( point, Constellation::Bpsk, false, FixedI32::<U20>::from_num(0.5), &mut llrs );
+/// assert_eq!{ llrs[0], 4 };
+/// ```
+pub fn demap_llrs<T>( point:Complex<T>, constellation:Constellation, unit_power:bool, noise_variance:T, llrs:&mut [T] )
+    where T: FixedSigned
+{
+    let scale = grid_scale::<T>( constellation, unit_power );
+    match constellation {
+        Constellation::Bpsk => pam_llrs( point.re, 2, scale, noise_variance, llrs ),
+        Constellation::Psk8 => {
+            // The points have equal power, so the nearest point with a bit value is the one of the largest correlation `Re(y·s*)`,
+            // and the difference of the squared distances is twice the difference of the correlations.
+            let (re, im) = (utility::wide_bits( point.re ), utility::wide_bits( point.im ));
+            for (j, llr) in llrs.iter_mut().take( 3 ).enumerate() {
+                let mut max_zero = i128::MIN;
+                let mut max_one  = i128::MIN;
+                for symbol in 0..8u8 {
+                    let s = map_symbol::<T>( symbol, constellation, unit_power );
+                    let correlation = (re*utility::wide_bits( s.re )).saturating_add( im*utility::wide_bits( s.im ) );
+                    if (symbol >> (2-j)) & 1 == 0
+                    {
+                        max_zero = max_zero.max( correlation );
+                    }
+                    else
+                    {
+                        max_one = max_one.max( correlation );
+                    }
+                }
+                *llr = utility::from_wide_bits( max_zero.saturating_sub( max_one ).saturating_mul(2)/utility::wide_bits( noise_variance ) );
+            }
+        },
+        _ => {
+            let levels = constellation.levels_per_axis();
+            let half   = constellation.bits_per_symbol()/2;
+            let (re_llrs, im_llrs) = llrs.split_at_mut( half.min(llrs.len()) );
+            pam_llrs( point.re, levels, scale, noise_variance, re_llrs );
+            pam_llrs( point.im, levels, scale, noise_variance, im_llrs );
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Constellation, map_symbol, demap_symbol, demap_llrs};
+    use fixed::{types::extra::U20, FixedI32};
+    use num::complex::Complex;
+
+    #[test]
+    fn unit_power_and_gray_neighbours() {
+        for constellation in [Constellation::Bpsk, Constellation::Qpsk, Constellation::Psk8, Constellation::Qam16, Constellation::Qam64].iter() {
+            let size = 1usize << constellation.bits_per_symbol();
+            let mut power = 0f64;
+            for symbol in 0..size {
+                let point = map_symbol::<FixedI32<U20>>( symbol as u8, *constellation, true );
+                power += (point.re*point.re+point.im*point.im).to_num::<f64>();
+
+                // Every nearest neighbour differs in a single bit.
+                let distance = |other:usize| -> f64 {
+                    let q = map_symbol::<FixedI32<U20>>( other as u8, *constellation, true );
+                    return ((point.re-q.re)*(point.re-q.re)+(point.im-q.im)*(point.im-q.im)).to_num::<f64>();
+                };
+                let nearest = (0..size).filter( |other| *other != symbol ).map( distance ).fold( f64::MAX, f64::min );
+                for other in (0..size).filter( |other| *other != symbol && distance(*other) < nearest+1e-3 ) {
+                    assert_eq!{ (symbol^other).count_ones(), 1 };
+                }
+            }
+            assert!{ (power/size as f64-1.0).abs() < 1e-3 };
+        }
+    }
+
+    #[test]
+    fn demapping_inverts_mapping() {
+        for constellation in [Constellation::Bpsk, Constellation::Qpsk, Constellation::Psk8, Constellation::Qam16, Constellation::Qam64].iter() {
+            let bits = constellation.bits_per_symbol();
+            for symbol in 0..(1u8 << bits) {
+                let mut point = map_symbol::<FixedI32<U20>>( symbol, *constellation, true );
+                // Nudge the point, less than half the minimum distance.
+                point.re += FixedI32::<U20>::from_num(0.05f32);
+                assert_eq!{ demap_symbol( point, *constellation, true ), symbol };
+
+                let mut llrs = [FixedI32::<U20>::from_num(0); 6];
+                demap_llrs( point, *constellation, true, FixedI32::<U20>::from_num(0.1f32), &mut llrs[0..bits] );
+                for (j, llr) in llrs[0..bits].iter().enumerate() {
+                    let bit = (symbol >> (bits-1-j)) & 1;
+                    assert!{ (bit == 0 && 0 < *llr) || (bit == 1 && *llr < 0) };
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large_inputs_decide_as_the_outer_points() {
+        let point = Complex::new( FixedI32::<U20>::from_num(-1000), FixedI32::<U20>::from_num(1000) );
+        assert_eq!{ demap_symbol( point, Constellation::Bpsk, true ), 0b1 };
+        assert_eq!{ demap_symbol( point, Constellation::Qpsk, true ), 0b10 };
+        assert_eq!{ demap_symbol( point, Constellation::Psk8, true ), 0b010 };
+        assert_eq!{ demap_symbol( point, Constellation::Qam16, true ), 0b1000 };
+        assert_eq!{ demap_symbol( point, Constellation::Qam64, true ), 0b100000 };
+    }
+
+    #[test]
+    fn large_soft_inputs_saturate() {
+        // The squared distances are beyond the range of the type for every constellation.
+        let point = Complex::new( FixedI32::<U20>::from_num(-1000), FixedI32::<U20>::from_num(1000) );
+        for constellation in [Constellation::Bpsk, Constellation::Qpsk, Constellation::Psk8, Constellation::Qam16, Constellation::Qam64].iter() {
+            let bits = constellation.bits_per_symbol();
+            let symbol = demap_symbol( point, *constellation, true );
+            let mut llrs = [FixedI32::<U20>::from_num(0); 6];
+            demap_llrs( point, *constellation, true, FixedI32::<U20>::from_num(0.1f32), &mut llrs[0..bits] );
+            for (j, llr) in llrs[0..bits].iter().enumerate() {
+                let bit = (symbol >> (bits-1-j)) & 1;
+                assert!{ (bit == 0 && 0 < *llr) || (bit == 1 && *llr < 0) };
+            }
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Moving-window statistics of streaming signals, updated in O(1) per sample.
+
+/// This macro implements the moving average, variance and RMS over a window of the N most recent samples.
+///
+/// The sum and the sum of squares of the window are kept in wide integers, and updated as samples enter and leave the window.
+/// They are thus exact, and neither drift nor overflow over long runs.
+/// Until N samples are pushed, the statistics cover the samples pushed so far.
+///
+/// ## Arguments
+/// * `name`     - The name of the type. E.g. MovingAverage.
+/// * `real_arr` - The name of the real array type of the blocks. E.g. Arr32.
+/// * `N`        - The window length.
+/// * `T`        - The fixed type of the elements.
+///
+/// # `::new` and `::reset`
+/// Generate or clear the window.
+///
+/// # `::push` and `::push_block`
+/// Push a sample, or a block of samples, e.g. the `data` of an array.
+///
+/// # `::mean`, `::variance` and `::rms`
+/// Get the statistics of the window. The variance is the population variance, `E[x²]-E[x]²`.
+///
+/// # `::process_mean`, `::process_variance` and `::process_rms`
+/// Push a block, and return the statistic after each sample of it.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_moving_average!( MovingAverage, Arr4, 2, FixedI32<U20> );
+///
+/// let mut average = MovingAverage::new();
+/// let x = Arr4::new_from_f32_array( [1.0, 3.0, -3.0, 5.0] );
+/// assert_eq!{ average.process_mean( &x ).as_array_f32(), [1.0, 2.0, 0.0, 1.0] };
+/// // The window now holds -3 and 5.
+/// assert_eq!{ average.variance(), 16 };
+/// assert_eq!{ average.rms().to_num::<f32>(), 4.123105 };
+/// ```
+#[macro_export]
+macro_rules! declare_moving_average{
+    ( $name:ident, $real_arr:ident, $N:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Moving average, variance and RMS.
+        pub struct $name{
+            history: [$T; $N],
+            position: usize,
+            count: usize,
+            // The sums of the bits of the samples, and of their squares.
+            sum: i128,
+            sum_squares: i128,
+        }
+
+        impl $name {
+            /// Generate an empty window.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                $name {
+                    history: [<$T>::from_num(0); $N],
+                    position: 0,
+                    count: 0,
+                    sum: 0,
+                    sum_squares: 0,
+                }
+            }
+            /// Clear the window.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new();
+            }
+            /// Push a sample, replacing the oldest one.
+            #[allow(dead_code)]
+            fn push( &mut self, item:$T ) {
+                let oldest = self.history[self.position].to_bits() as i128;
+                let newest = item.to_bits() as i128;
+                self.sum += newest-oldest;
+                self.sum_squares += newest*newest-oldest*oldest;
+                self.history[self.position] = item;
+                self.position = (self.position+1)%$N;
+                if self.count < $N
+                {
+                    self.count += 1;
+                }
+            }
+            /// Push a block of samples.
+            #[allow(dead_code)]
+            fn push_block( &mut self, input:&[$T] ) {
+                for item in input.iter() {
+                    self.push( *item );
+                }
+            }
+            /// The mean of the window.
+            #[allow(dead_code)]
+            fn mean( &self ) -> $T {
+                if self.count == 0
+                {
+                    return <$T>::from_num(0);
+                }
+                return integer_array::utility::from_wide_bits::<$T>( self.sum/(self.count as i128) );
+            }
+            /// The mean square of the window, with twice the fractional bits of the type.
+            #[allow(dead_code)]
+            fn mean_square_bits( &self ) -> i128 {
+                if self.count == 0
+                {
+                    return 0;
+                }
+                return self.sum_squares/(self.count as i128);
+            }
+            /// The population variance of the window.
+            #[allow(dead_code)]
+            fn variance( &self ) -> $T {
+                if self.count == 0
+                {
+                    return <$T>::from_num(0);
+                }
+                let count = self.count as i128;
+                // (nΣx²-(Σx)²)/n², with twice the fractional bits of the type.
+                let bits = (count*self.sum_squares-self.sum*self.sum)/(count*count);
+                return integer_array::utility::from_wide_bits::<$T>( bits >> <$T>::FRAC_NBITS );
+            }
+            /// The root mean square of the window.
+            #[allow(dead_code)]
+            fn rms( &self ) -> $T {
+                return integer_array::utility::from_wide_bits::<$T>( (self.mean_square_bits() as u128).isqrt() as i128 );
+            }
+            /// Push a block, and return the mean after each sample.
+            #[allow(dead_code)]
+            fn process_mean( &mut self, input:&$real_arr ) -> $real_arr {
+                let mut r_array = *input;
+                for item in r_array.data.iter_mut() {
+                    self.push( *item );
+                    *item = self.mean();
+                }
+                return r_array;
+            }
+            /// Push a block, and return the variance after each sample.
+            #[allow(dead_code)]
+            fn process_variance( &mut self, input:&$real_arr ) -> $real_arr {
+                let mut r_array = *input;
+                for item in r_array.data.iter_mut() {
+                    self.push( *item );
+                    *item = self.variance();
+                }
+                return r_array;
+            }
+            /// Push a block, and return the RMS after each sample.
+            #[allow(dead_code)]
+            fn process_rms( &mut self, input:&$real_arr ) -> $real_arr {
+                let mut r_array = *input;
+                for item in r_array.data.iter_mut() {
+                    self.push( *item );
+                    *item = self.rms();
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+/// This macro implements the moving minimum and maximum over a window of the N most recent samples.
+///
+/// Each extreme is tracked by a monotonic deque of the samples that may still become the extreme of the window.
+/// A new sample removes the samples it dominates from the back, and samples leaving the window are removed from the front.
+/// Each sample enters and leaves a deque once, so the update is O(1) amortized, and the extremes are read in O(1).
+/// Until N samples are pushed, the extremes cover the samples pushed so far.
+///
+/// ## Arguments
+/// * `name`     - The name of the type. E.g. MovingMinMax.
+/// * `real_arr` - The name of the real array type of the blocks. E.g. Arr32.
+/// * `N`        - The window length.
+/// * `T`        - The fixed type of the elements.
+///
+/// # `::new` and `::reset`
+/// Generate or clear the window.
+///
+/// # `::push` and `::push_block`
+/// Push a sample, or a block of samples, e.g. the `data` of an array.
+///
+/// # `::min` and `::max`
+/// Get the extremes of the window. An empty window gives the maximum and the minimum value of the type, as `min` and `max` on arrays do.
+///
+/// # `::process_min` and `::process_max`
+/// Push a block, and return the extreme after each sample of it.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr6, 6, FixedI32<U20> );
+/// ia::declare_moving_min_max!( MovingMinMax, Arr6, 3, FixedI32<U20> );
+///
+/// let x = Arr6::new_from_f32_array( [4.0, 2.0, 5.0, 1.0, 3.0, 3.0] );
+/// let mut extremes = MovingMinMax::new();
+/// assert_eq!{ extremes.process_max( &x ).as_array_f32(), [4.0, 4.0, 5.0, 5.0, 5.0, 3.0] };
+/// extremes.reset();
+/// assert_eq!{ extremes.process_min( &x ).as_array_f32(), [4.0, 2.0, 2.0, 1.0, 1.0, 1.0] };
+/// ```
+#[macro_export]
+macro_rules! declare_moving_min_max{
+    ( $name:ident, $real_arr:ident, $N:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Moving minimum and maximum.
+        pub struct $name{
+            // Ring buffers of the deques of the minimum [0] and the maximum [1].
+            times: [[usize; $N]; 2],
+            values: [[$T; $N]; 2],
+            heads: [usize; 2],
+            lengths: [usize; 2],
+            time: usize,
+        }
+
+        impl $name {
+            /// Generate an empty window.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                $name {
+                    times: [[0; $N]; 2],
+                    values: [[<$T>::from_num(0); $N]; 2],
+                    heads: [0; 2],
+                    lengths: [0; 2],
+                    time: 0,
+                }
+            }
+            /// Clear the window.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new();
+            }
+            /// Update the deque of the minimum (0) or the maximum (1) with a sample.
+            #[allow(dead_code)]
+            fn update( &mut self, deque:usize, item:$T ) {
+                // Remove the samples dominated by the new one from the back.
+                while 0 < self.lengths[deque]
+                {
+                    let back = self.values[deque][(self.heads[deque]+self.lengths[deque]-1)%$N];
+                    let dominated = if deque == 0 { item <= back } else { back <= item };
+                    if !dominated
+                    {
+                        break;
+                    }
+                    self.lengths[deque] -= 1;
+                }
+                // Remove the sample leaving the window from the front.
+                if 0 < self.lengths[deque] && $N <= self.time.wrapping_sub( self.times[deque][self.heads[deque]] )
+                {
+                    self.heads[deque] = (self.heads[deque]+1)%$N;
+                    self.lengths[deque] -= 1;
+                }
+                let back = (self.heads[deque]+self.lengths[deque])%$N;
+                self.times[deque][back]  = self.time;
+                self.values[deque][back] = item;
+                self.lengths[deque] += 1;
+            }
+            /// Push a sample, replacing the oldest one.
+            #[allow(dead_code)]
+            fn push( &mut self, item:$T ) {
+                self.update( 0, item );
+                self.update( 1, item );
+                self.time = self.time.wrapping_add(1);
+            }
+            /// Push a block of samples.
+            #[allow(dead_code)]
+            fn push_block( &mut self, input:&[$T] ) {
+                for item in input.iter() {
+                    self.push( *item );
+                }
+            }
+            /// The minimum of the window.
+            #[allow(dead_code)]
+            fn min( &self ) -> $T {
+                if self.lengths[0] == 0
+                {
+                    return <$T>::MAX;
+                }
+                return self.values[0][self.heads[0]];
+            }
+            /// The maximum of the window.
+            #[allow(dead_code)]
+            fn max( &self ) -> $T {
+                if self.lengths[1] == 0
+                {
+                    return <$T>::MIN;
+                }
+                return self.values[1][self.heads[1]];
+            }
+            /// Push a block, and return the minimum after each sample.
+            #[allow(dead_code)]
+            fn process_min( &mut self, input:&$real_arr ) -> $real_arr {
+                let mut r_array = *input;
+                for item in r_array.data.iter_mut() {
+                    self.push( *item );
+                    *item = self.min();
+                }
+                return r_array;
+            }
+            /// Push a block, and return the maximum after each sample.
+            #[allow(dead_code)]
+            fn process_max( &mut self, input:&$real_arr ) -> $real_arr {
+                let mut r_array = *input;
+                for item in r_array.data.iter_mut() {
+                    self.push( *item );
+                    *item = self.max();
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+/// This macro implements a sliding median filter over a window of the W most recent samples.
+///
+/// The window is kept both in arrival order and in sorted order.
+/// Each sample removes the oldest sample from the sorted window and inserts itself, by binary search, in O(W) without allocation.
+/// For even window lengths, the median is the mean of the two middle samples.
+/// Until W samples are pushed, the median covers the samples pushed so far.
+///
+/// ## Arguments
+/// * `name`     - The name of the filter type. E.g. MedianFilter.
+/// * `real_arr` - The name of the real array type of the blocks. E.g. Arr32.
+/// * `W`        - The window length.
+/// * `T`        - The fixed type of the elements.
+///
+/// # `::new` and `::reset`
+/// Generate or clear the filter.
+///
+/// # `::push` and `::process`
+/// Push a sample and return the median of the window, or filter a block of samples.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr8, 8, FixedI32<U20> );
+/// ia::declare_median_filter!( MedianFilter, Arr8, 3, FixedI32<U20> );
+///
+/// // Impulsive noise is removed.
+/// let x = Arr8::new_from_f32_array( [1.0, 1.0, 9.0, 1.0, 2.0, 2.0, -7.0, 2.0] );
+/// let mut filter = MedianFilter::new();
+/// assert_eq!{ filter.process( &x ).as_array_f32(), [1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0] };
+/// ```
+#[macro_export]
+macro_rules! declare_median_filter{
+    ( $name:ident, $real_arr:ident, $W:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Sliding median filter.
+        pub struct $name{
+            history: [$T; $W],
+            sorted: [$T; $W],
+            position: usize,
+            count: usize,
+        }
+
+        impl $name {
+            /// Generate an empty filter.
+            #[allow(dead_code)]
+            fn new() -> Self {
+                $name {
+                    history: [<$T>::from_num(0); $W],
+                    sorted: [<$T>::from_num(0); $W],
+                    position: 0,
+                    count: 0,
+                }
+            }
+            /// Clear the filter.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                *self = Self::new();
+            }
+            /// Push a sample, and return the median of the window.
+            #[allow(dead_code)]
+            fn push( &mut self, item:$T ) -> $T {
+                if self.count == $W
+                {
+                    // Remove the oldest sample from the sorted window.
+                    let oldest = self.history[self.position];
+                    let index = self.sorted.partition_point( |x| *x < oldest );
+                    self.sorted.copy_within( index+1.., index );
+                    self.count -= 1;
+                }
+                let index = self.sorted[..self.count].partition_point( |x| *x < item );
+                self.sorted.copy_within( index..self.count, index+1 );
+                self.sorted[index] = item;
+                self.count += 1;
+                self.history[self.position] = item;
+                self.position = (self.position+1)%$W;
+
+                let upper = self.sorted[self.count/2];
+                if self.count%2 == 1
+                {
+                    return upper;
+                }
+                let lower = self.sorted[self.count/2-1];
+                return integer_array::utility::from_wide_bits::<$T>( (lower.to_bits() as i128+upper.to_bits() as i128)/2 );
+            }
+            /// Filter a block of samples.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$real_arr ) -> $real_arr {
+                let mut r_array = *input;
+                for item in r_array.data.iter_mut() {
+                    *item = self.push( *item );
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+
+    // A pseudo-random sequence in the -1..1 range.
+    fn sequence() -> [f64; 64] {
+        let mut state:u32 = 12345;
+        let mut r_array = [0.0; 64];
+        for item in r_array.iter_mut() {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            *item = ((state >> 8) as f64)/((1u32 << 23) as f64)-1.0;
+        }
+        return r_array;
+    }
+
+    #[test]
+    fn moving_statistics_match_the_window() {
+        integer_array::declare_array_real!( Arr16, 16, FixedI32<U20> );
+        integer_array::declare_moving_average!( MovingAverage, Arr16, 5, FixedI32<U20> );
+        integer_array::declare_moving_min_max!( MovingMinMax, Arr16, 5, FixedI32<U20> );
+
+        let x = sequence().map( FixedI32::<U20>::from_num );
+        let mut average  = MovingAverage::new();
+        let mut extremes = MovingMinMax::new();
+        for n in 0..64 {
+            average.push( x[n] );
+            extremes.push( x[n] );
+            let window = &x[n.saturating_sub(4)..=n];
+            let length = window.len() as f64;
+            let mean = window.iter().map( |item| item.to_num::<f64>() ).sum::<f64>()/length;
+            let mean_square = window.iter().map( |item| item.to_num::<f64>().powi(2) ).sum::<f64>()/length;
+            assert!{ (average.mean().to_num::<f64>()-mean).abs() < 1e-5 };
+            assert!{ (average.variance().to_num::<f64>()-(mean_square-mean*mean)).abs() < 1e-5 };
+            assert!{ (average.rms().to_num::<f64>()-mean_square.sqrt()).abs() < 1e-5 };
+            assert_eq!{ extremes.min(), *window.iter().min().unwrap() };
+            assert_eq!{ extremes.max(), *window.iter().max().unwrap() };
+        }
+    }
+
+    #[test]
+    fn median_filter_matches_the_sorted_window() {
+        integer_array::declare_array_real!( Arr16, 16, FixedI32<U20> );
+        integer_array::declare_median_filter!( MedianFilter4, Arr16, 4, FixedI32<U20> );
+        integer_array::declare_median_filter!( MedianFilter5, Arr16, 5, FixedI32<U20> );
+
+        // Coarse values, so the windows hold duplicates.
+        let x = sequence().map( |item| FixedI32::<U20>::from_num( (item*4.0).round() ) );
+        let mut filter4 = MedianFilter4::new();
+        let mut filter5 = MedianFilter5::new();
+        for n in 0..64 {
+            for (length, median) in [(4, filter4.push( x[n] )), (5, filter5.push( x[n] ))].iter() {
+                let mut window = [0.0; 5];
+                let start = (n+1).saturating_sub(*length);
+                for (item, value) in window.iter_mut().zip( x[start..=n].iter() ) {
+                    *item = value.to_num::<f64>();
+                }
+                let window = &mut window[..n+1-start];
+                window.sort_by( |a, b| a.partial_cmp(b).unwrap() );
+                let middle = window.len()/2;
+                let expected = if window.len()%2 == 1 { window[middle] } else { (window[middle-1]+window[middle])/2.0 };
+                assert_eq!{ median.to_num::<f64>(), expected };
+            }
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use fixed::traits::FixedSigned;
+use num::complex::Complex;
+use crate::utility;
+
+/// The modulation tracked by a Costas loop.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Modulation {
+    /// Binary phase shift keying, symbols on the real axis.
+    Bpsk,
+    /// Quadrature phase shift keying, symbols on the diagonals.
+    Qpsk,
+}
+
+/// Calculate the proportional and integral gains of a second-order loop filter.
+///
+/// The gains assume a phase detector and NCO with unity gain, i.e. a phase error of φ radians gives an error signal of φ.
+///
+/// ## Arguments
+/// * `bandwidth` - The noise bandwidth of the loop, relative to the sample rate. E.g. 0.01.
+/// * `damping`   - The damping factor ζ of the loop. E.g. 0.707.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::pll;
+///
+/// let (proportional, integral) = pll::loop_gains( FixedI32::<U20>::from_num(0.01), FixedI32::<U20>::from_num(0.707) );
+/// assert_eq!{ proportional.to_num::<f32>(), 0.02630806 };
+/// assert_eq!{ integral.to_num::<f32>(), 0.00034999847 };
+/// ```
+pub fn loop_gains<T>( bandwidth:T, damping:T ) -> (T, T)
+    where T: FixedSigned
+{
+    let one   = T::from_num(1);
+    let four  = T::from_num(4);
+    let theta = bandwidth/(damping+one/(four*damping));
+    let denominator = one+T::from_num(2)*damping*theta+theta*theta;
+    return ( four*damping*theta/denominator, four*theta*theta/denominator );
+}
+
+/// Rotate a complex sample by -φ, `z·e^(-jφ)`.
+///
+/// ## Arguments
+/// * `item`  - The sample to rotate.
+/// * `phase` - The phase φ in radians.
+pub fn derotate<T>( item:Complex<T>, phase:T ) -> Complex<T>
+    where T: FixedSigned
+{
+    let cos = utility::cos_precise_fixed( phase );
+    let sin = utility::sin_precise_fixed( phase );
+    return Complex::new( item.re*cos+item.im*sin, item.im*cos-item.re*sin );
+}
+
+/// The Costas phase detector for a derotated symbol of unit magnitude.
+///
+/// * BPSK uses `re·im`, which is `sin(2φ)/2`.
+/// * QPSK uses `(sign(re)·im-sign(im)·re)/√2`, which is `sin(φ)` for symbols on the diagonals.
+///
+/// Both are close to φ for small phase errors.
+pub fn costas_error<T>( item:Complex<T>, modulation:Modulation ) -> T
+    where T: FixedSigned
+{
+    match modulation {
+        Modulation::Bpsk => item.re*item.im,
+        Modulation::Qpsk => ( item.im*item.re.signum()-item.re*item.im.signum() )*T::from_num(fixed::consts::FRAC_1_SQRT_2),
+    }
+}
+
+/// This macro implements a second-order phase-locked loop (PLL) for complex arrays.
+///
+/// Each sample is derotated by the phase of a numerically controlled oscillator (NCO).
+/// The phase error is the argument of the derotated sample, calculated as in `arg()`.
+/// A proportional-integral loop filter steers the frequency and phase of the NCO, and the phase is kept in range with `wrap_phase`.
+///
+/// The NCO phase and frequency are kept across blocks.
+///
+/// ## Arguments
+/// * `name`        - The name of the PLL type. E.g. Pll.
+/// * `complex_arr` - The name of the complex array type to process. E.g. CArr32.
+/// * `real_arr`    - The name of the real counterpart of the complex array type, holding the phase error. E.g. Arr32.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate a PLL with zero phase and frequency.
+///
+/// ## Arguments
+/// * `bandwidth` - The noise bandwidth of the loop, relative to the sample rate.
+/// * `damping`   - The damping factor of the loop.
+///
+/// # `::process`
+/// Track a block of samples. Returns the derotated samples and the phase error of each sample.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr32, Arr32, 32, FixedI32<U20> );
+/// ia::declare_pll!( Pll, CArr32, Arr32, FixedI32<U20> );
+///
+/// let mut pll = Pll::new( FixedI32::<U20>::from_num(0.05), FixedI32::<U20>::from_num(0.707) );
+/// // A carrier with a phase offset of 0.5 radians.
+/// let x = CArr32::new_from_f32( 0.87758255, 0.47942555 );
+/// for _i in 0..8 {
+///     pll.process( &x );
+/// }
+/// let (y, error) = pll.process( &x );
+/// assert!{ 0.499 < pll.phase() && pll.phase() < 0.501 };
+/// assert!{ 0.999 < y[31].re && y[31].im.abs() < 0.001 };
+/// assert!{ error[31].abs() < 0.001 };
+/// ```
+///
+/// # `::phase`, `::frequency` and `::set_frequency`
+/// Get the NCO phase in radians, get the NCO frequency in radians per sample, or set the NCO frequency, e.g. from a coarse estimate.
+///
+/// # `::reset`
+/// Clear the phase and frequency of the NCO.
+#[macro_export]
+macro_rules! declare_pll{
+    ( $name:ident, $complex_arr:ident, $real_arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Second-order phase-locked loop.
+        pub struct $name{
+            proportional: $T,
+            integral: $T,
+            phase: $T,
+            frequency: $T,
+        }
+
+        impl $name {
+            /// Generate a PLL with zero phase and frequency.
+            #[allow(dead_code)]
+            fn new( bandwidth:$T, damping:$T ) -> Self {
+                let (proportional, integral) = integer_array::pll::loop_gains( bandwidth, damping );
+                $name {
+                    proportional,
+                    integral,
+                    phase: <$T>::from_num(0),
+                    frequency: <$T>::from_num(0),
+                }
+            }
+            /// Clear the phase and frequency of the NCO.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.phase     = <$T>::from_num(0);
+                self.frequency = <$T>::from_num(0);
+            }
+            /// Returns the NCO phase in radians.
+            #[allow(dead_code)]
+            fn phase( &self ) -> $T {
+                return self.phase;
+            }
+            /// Returns the NCO frequency in radians per sample.
+            #[allow(dead_code)]
+            fn frequency( &self ) -> $T {
+                return self.frequency;
+            }
+            /// Set the NCO frequency in radians per sample.
+            #[allow(dead_code)]
+            fn set_frequency( &mut self, frequency:$T ) {
+                self.frequency = frequency;
+            }
+            /// Track a block of samples. Returns the derotated samples and the phase error of each sample.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$complex_arr ) -> ($complex_arr, $real_arr) {
+                let mut r_array = $complex_arr::new_from_i32( 0, 0 );
+                let mut error   = $real_arr::new_from_i32( 0 );
+                for n in 0..input.data.len() {
+                    let item = integer_array::pll::derotate( input[n], self.phase );
+                    error[n] = integer_array::utility::atan2_fixed( item.im, item.re );
+                    self.frequency += self.integral*error[n];
+                    self.phase = fixed_trigonometry::wrap_phase( self.phase+self.frequency+self.proportional*error[n] );
+                    r_array.data[n] = item;
+                }
+                return (r_array, error);
+            }
+        }
+    }
+}
+
+/// This macro implements a Costas loop for carrier recovery of BPSK and QPSK modulated complex arrays.
+///
+/// The loop is identical to the PLL in `declare_pll`, except for the phase detector, which is insensitive to the symbol modulation.
+/// See `pll::costas_error`. The detector gain assumes symbols of unit magnitude, e.g. after an AGC.
+/// The loop locks with an ambiguity of π for BPSK and π/2 for QPSK.
+///
+/// ## Arguments
+/// * `name`        - The name of the Costas loop type. E.g. Costas.
+/// * `complex_arr` - The name of the complex array type to process. E.g. CArr32.
+/// * `real_arr`    - The name of the real counterpart of the complex array type, holding the phase error. E.g. Arr32.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate a Costas loop with zero phase and frequency.
+///
+/// ## Arguments
+/// * `bandwidth`  - The noise bandwidth of the loop, relative to the sample rate.
+/// * `damping`    - The damping factor of the loop.
+/// * `modulation` - The modulation to track, `pll::Modulation::Bpsk` or `pll::Modulation::Qpsk`.
+///
+/// # `::process`, `::phase`, `::frequency`, `::set_frequency` and `::reset`
+/// See `declare_pll`.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr4, Arr4, 4, FixedI32<U20> );
+/// ia::declare_costas_loop!( Costas, CArr4, Arr4, FixedI32<U20> );
+///
+/// let mut costas = Costas::new( FixedI32::<U20>::from_num(0.05), FixedI32::<U20>::from_num(0.707), ia::pll::Modulation::Bpsk );
+/// // BPSK symbols with a phase offset of 0.3 radians.
+/// let mut x = CArr4::new_from_f32( 0.9553365, 0.29552022 );
+/// x.data[1] = num::complex::Complex::new( -x[1].re, -x[1].im );
+/// x.data[2] = num::complex::Complex::new( -x[2].re, -x[2].im );
+/// for _i in 0..64 {
+///     costas.process( &x );
+/// }
+/// let (y, _error) = costas.process( &x );
+/// assert!{ 0.299 < costas.phase() && costas.phase() < 0.301 };
+/// assert!{ -1.001 < y[1].re && y[1].re < -0.999 };
+/// ```
+#[macro_export]
+macro_rules! declare_costas_loop{
+    ( $name:ident, $complex_arr:ident, $real_arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Costas loop for BPSK and QPSK carrier recovery.
+        pub struct $name{
+            proportional: $T,
+            integral: $T,
+            phase: $T,
+            frequency: $T,
+            modulation: integer_array::pll::Modulation,
+        }
+
+        impl $name {
+            /// Generate a Costas loop with zero phase and frequency.
+            #[allow(dead_code)]
+            fn new( bandwidth:$T, damping:$T, modulation:integer_array::pll::Modulation ) -> Self {
+                let (proportional, integral) = integer_array::pll::loop_gains( bandwidth, damping );
+                $name {
+                    proportional,
+                    integral,
+                    phase: <$T>::from_num(0),
+                    frequency: <$T>::from_num(0),
+                    modulation,
+                }
+            }
+            /// Clear the phase and frequency of the NCO.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.phase     = <$T>::from_num(0);
+                self.frequency = <$T>::from_num(0);
+            }
+            /// Returns the NCO phase in radians.
+            #[allow(dead_code)]
+            fn phase( &self ) -> $T {
+                return self.phase;
+            }
+            /// Returns the NCO frequency in radians per sample.
+            #[allow(dead_code)]
+            fn frequency( &self ) -> $T {
+                return self.frequency;
+            }
+            /// Set the NCO frequency in radians per sample.
+            #[allow(dead_code)]
+            fn set_frequency( &mut self, frequency:$T ) {
+                self.frequency = frequency;
+            }
+            /// Track a block of symbols. Returns the derotated symbols and the phase error of each symbol.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$complex_arr ) -> ($complex_arr, $real_arr) {
+                let mut r_array = $complex_arr::new_from_i32( 0, 0 );
+                let mut error   = $real_arr::new_from_i32( 0 );
+                for n in 0..input.data.len() {
+                    let item = integer_array::pll::derotate( input[n], self.phase );
+                    error[n] = integer_array::pll::costas_error( item, self.modulation );
+                    self.frequency += self.integral*error[n];
+                    self.phase = fixed_trigonometry::wrap_phase( self.phase+self.frequency+self.proportional*error[n] );
+                    r_array.data[n] = item;
+                }
+                return (r_array, error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use fixed::{types::extra::U20, FixedI32};
+
+    #[test]
+    fn pll_tracks_frequency_offset_across_blocks() {
+        integer_array::declare_array_complex!( CArr16, Arr16, 16, FixedI32<U20> );
+        integer_array::declare_pll!( Pll, CArr16, Arr16, FixedI32<U20> );
+
+        let omega = 0.05f64;
+        let mut pll = Pll::new( FixedI32::<U20>::from_num(0.02), FixedI32::<U20>::from_num(0.707) );
+        let mut error = Arr16::new_from_i32( 0 );
+        for block in 0..40 {
+            let mut x = CArr16::new_from_i32( 0, 0 );
+            for (n, item) in x.data.iter_mut().enumerate() {
+                let phase = omega*(16*block+n) as f64+2.0;
+                *item = num::complex::Complex::new( FixedI32::<U20>::from_num(phase.cos()), FixedI32::<U20>::from_num(phase.sin()) );
+            }
+            error = pll.process( &x ).1;
+        }
+        assert!{ (pll.frequency().to_num::<f64>()-omega).abs() < 0.001 };
+        assert!{ error[15].abs() < 0.01 };
+    }
+
+    #[test]
+    fn costas_tracks_qpsk() {
+        integer_array::declare_array_complex!( CArr16, Arr16, 16, FixedI32<U20> );
+        integer_array::declare_costas_loop!( Costas, CArr16, Arr16, FixedI32<U20> );
+
+        let omega = 0.01f64;
+        let mut costas = Costas::new( FixedI32::<U20>::from_num(0.02), FixedI32::<U20>::from_num(0.707), integer_array::pll::Modulation::Qpsk );
+        let mut seed = 1u32;
+        let mut y = CArr16::new_from_i32( 0, 0 );
+        for block in 0..40 {
+            let mut x = CArr16::new_from_i32( 0, 0 );
+            for (n, item) in x.data.iter_mut().enumerate() {
+                seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                let symbol = core::f64::consts::FRAC_PI_4*(2*(seed>>16)%8+1) as f64;
+                let phase = symbol+omega*(16*block+n) as f64+0.3;
+                *item = num::complex::Complex::new( FixedI32::<U20>::from_num(phase.cos()), FixedI32::<U20>::from_num(phase.sin()) );
+            }
+            y = costas.process( &x ).0;
+        }
+        assert!{ (costas.frequency().to_num::<f64>()-omega).abs() < 0.001 };
+        for item in y.data.iter() {
+            assert!{ (item.re.abs().to_num::<f64>()-core::f64::consts::FRAC_1_SQRT_2).abs() < 0.01 };
+            assert!{ (item.im.abs().to_num::<f64>()-core::f64::consts::FRAC_1_SQRT_2).abs() < 0.01 };
+        }
+    }
+}
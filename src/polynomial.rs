@@ -0,0 +1,249 @@
+//! Polynomials with fixed-point coefficients.
+//!
+//! `Polynomial<T, N>` holds the N coefficients of a polynomial of degree N-1, in ascending powers,
+//! `p(x) = c[0] + c[1]·x + .. + c[N-1]·x^(N-1)`.
+//! Polynomials are evaluated by Horner's method in wide integers, rounding once per coefficient,
+//! so intermediate values may leave the range of the type as long as the result does not.
+
+use fixed::traits::{Fixed, FixedSigned};
+use crate::utility::{wide_bits, from_wide_bits};
+use crate::linalg::SolveError;
+use crate::matrix::{Element, Matrix};
+
+/// A polynomial of degree N-1 with fixed-point coefficients, in ascending powers.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::polynomial::Polynomial;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+///
+/// // p(x) = 2 - 3x + x², with the roots 1 and 2.
+/// let p = Polynomial::new( [2.0, -3.0, 1.0].map( FixedI32::<U20>::from_num ) );
+/// assert_eq!{ p.evaluate( FixedI32::<U20>::from_num(3) ), 2 };
+/// let y:Arr4 = p.evaluate_array( &Arr4::new_from_f32_array( [0.0, 1.0, 1.5, 4.0] ) );
+/// assert_eq!{ y.as_array_f32(), [2.0, 0.0, -0.25, 6.0] };
+///
+/// // p'(x) = -3 + 2x
+/// assert_eq!{ p.derivative().coefficients, [-3, 2, 0] };
+///
+/// // (2 - 3x + x²)·(1 + x) = 2 - x - 2x² + x³
+/// let q:Polynomial<FixedI32<U20>, 4> = p.mul( &Polynomial::new( [1.0, 1.0].map( FixedI32::<U20>::from_num ) ) );
+/// assert_eq!{ q.coefficients, [2, -1, -2, 1] };
+///
+/// let mut roots = [FixedI32::<U20>::from_num(0); 3];
+/// let count = q.real_roots( FixedI32::<U20>::from_num(-4), FixedI32::<U20>::from_num(4), &mut roots );
+/// assert_eq!{ roots[..count], [-1, 1, 2] };
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Polynomial<T, const N:usize> {
+    pub coefficients: [T; N],
+}
+
+impl<T, const N:usize> Polynomial<T, N>
+    where T: Fixed
+{
+    /// Generate a polynomial from its coefficients, in ascending powers.
+    pub fn new( coefficients:[T; N] ) -> Self {
+        return Polynomial { coefficients };
+    }
+    /// The degree, disregarding zero leading coefficients. The zero polynomial has degree zero.
+    pub fn degree( &self ) -> usize {
+        return self.coefficients.iter().rposition( |c| *c != T::ZERO ).unwrap_or(0);
+    }
+    /// Evaluate the polynomial at x by Horner's method. The result saturates at the range of the type.
+    pub fn evaluate( &self, x:T ) -> T {
+        let x = wide_bits(x);
+        let half = match T::FRAC_NBITS { 0 => 0, bits => 1i128 << (bits-1) };
+        let mut sum:i128 = 0;
+        for c in self.coefficients.iter().rev() {
+            sum = (sum.saturating_mul( x ).saturating_add( half ) >> T::FRAC_NBITS).saturating_add( wide_bits(*c) );
+        }
+        return from_wide_bits( sum );
+    }
+    /// Evaluate the polynomial at each item of an array.
+    pub fn evaluate_array<A, const M:usize>( &self, x:&A ) -> A
+        where A: AsRef<[T; M]> + From<[T; M]>
+    {
+        let mut r_array = *x.as_ref();
+        for item in r_array.iter_mut() {
+            *item = self.evaluate( *item );
+        }
+        return A::from( r_array );
+    }
+    /// The derivative. It is of the same length, with a zero leading coefficient.
+    pub fn derivative( &self ) -> Self {
+        let mut r_poly = Polynomial { coefficients: [T::ZERO; N] };
+        for (n, item) in r_poly.coefficients.iter_mut().enumerate().take( N.saturating_sub(1) ) {
+            *item = from_wide_bits( wide_bits(self.coefficients[n+1])*(n as i128+1) );
+        }
+        return r_poly;
+    }
+    /// The product with a polynomial of M coefficients, into K coefficients. K must be at least N+M-1.
+    pub fn mul<const M:usize, const K:usize>( &self, other:&Polynomial<T, M> ) -> Polynomial<T, K> {
+        assert!{ N+M-1 <= K };
+        let mut sums = [0i128; K];
+        for (n, a) in self.coefficients.iter().enumerate() {
+            for (m, b) in other.coefficients.iter().enumerate() {
+                sums[n+m] = sums[n+m].saturating_add( wide_bits(*a).saturating_mul( wide_bits(*b) ) );
+            }
+        }
+        let half = match T::FRAC_NBITS { 0 => 0, bits => 1i128 << (bits-1) };
+        return Polynomial { coefficients: sums.map( |sum| from_wide_bits( sum.saturating_add( half ) >> T::FRAC_NBITS ) ) };
+    }
+}
+
+impl<T, const N:usize> Polynomial<T, N>
+    where T: FixedSigned + Element<Real = T>
+{
+    /// Fit a polynomial to M points `(x, y)` in the least-squares sense, by QR factorization of the Vandermonde matrix.
+    ///
+    /// The fit is best conditioned with the x values scaled to about [-1, 1].
+    /// Fails if there are fewer distinct x values than coefficients, or the fit is too ill-conditioned for the type.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use integer_array as ia;
+    /// use ia::trait_definitions::*;
+    /// use ia::polynomial::Polynomial;
+    /// use fixed::{types::extra::U20, FixedI32};
+    ///
+    /// ia::declare_array_real!( Arr5, 5, FixedI32<U20> );
+    ///
+    /// let x = Arr5::new_from_f32_array( [-1.0, -0.5, 0.0, 0.5, 1.0] );
+    /// let y = Arr5::new_from_f32_array( [0.5, -0.25, -0.5, -0.25, 0.5] );
+    /// let p = Polynomial::<FixedI32<U20>, 3>::fit( &x, &y ).unwrap();
+    /// for (c, expected) in p.coefficients.iter().zip( [-0.5, 0.0, 1.0] ) {
+    ///     assert!{ (c.to_num::<f32>()-expected).abs() < 0.0001 };
+    /// }
+    /// ```
+    pub fn fit<A, const M:usize>( x:&A, y:&A ) -> Result<Self, SolveError>
+        where A: AsRef<[T; M]>
+    {
+        let mut vandermonde = Matrix::<T, M, N>::zeros();
+        for (row, x) in vandermonde.data.iter_mut().zip( x.as_ref().iter() ) {
+            let mut power = T::from_num(1);
+            for item in row.iter_mut() {
+                *item = power;
+                power = power.saturating_mul( *x );
+            }
+        }
+        let coefficients:[T; N] = vandermonde.qr()?.least_squares( y )?;
+        return Ok( Polynomial { coefficients } );
+    }
+    /// Find the real roots in `[low, high]`, write them into `roots` in ascending order, and return their number.
+    ///
+    /// The roots of the derivative split the interval into monotonic sections, and a section whose ends differ in sign is bisected to the resolution of the type.
+    /// Roots of even multiplicity, which do not change the sign, are found where the polynomial evaluates to zero.
+    /// Roots beyond the length of `roots` are dropped.
+    pub fn real_roots( &self, low:T, high:T, roots:&mut [T] ) -> usize {
+        let degree = self.degree();
+        if degree == 0 || high < low
+        {
+            return 0;
+        }
+        // The section ends: the interval ends, and the critical points within them.
+        let mut ends = [T::ZERO; N];
+        ends[0] = low;
+        let critical_count = match degree {
+            1 => 0,
+            _ => self.derivative().real_roots( low, high, &mut ends[1..] ),
+        };
+        let mut count = 0;
+        let mut push = |root:T, roots:&mut [T]| {
+            if count < roots.len() && ( count == 0 || roots[count-1] != root )
+            {
+                roots[count] = root;
+                count += 1;
+            }
+        };
+        let section_count = critical_count+1;
+        for n in 0..section_count {
+            let mut a = ends[n];
+            let mut b = if n+1 < section_count { ends[n+1] } else { high };
+            let (mut value_a, value_b) = (self.evaluate( a ), self.evaluate( b ));
+            if value_a == 0
+            {
+                push( a, roots );
+            }
+            if (value_a < 0 && value_b > 0) || (value_a > 0 && value_b < 0)
+            {
+                while wide_bits(T::DELTA) < wide_bits(b)-wide_bits(a) {
+                    let middle = from_wide_bits( (wide_bits(a)+wide_bits(b)) >> 1 );
+                    let value = self.evaluate( middle );
+                    if value == 0
+                    {
+                        a = middle;
+                        b = middle;
+                        break;
+                    }
+                    if (value < 0) == (value_a < 0)
+                    {
+                        a = middle;
+                        value_a = value;
+                    }
+                    else
+                    {
+                        b = middle;
+                    }
+                }
+                let root = if wide_bits( self.evaluate( a ) ).abs() <= wide_bits( self.evaluate( b ) ).abs() { a } else { b };
+                push( root, roots );
+            }
+            if value_b == 0
+            {
+                push( b, roots );
+            }
+        }
+        return count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Polynomial;
+    use fixed::{types::extra::U20, FixedI32};
+
+    type T = FixedI32<U20>;
+
+    #[test]
+    fn roots_of_a_product_are_found() {
+        // (x+1.5)·(x-0.25)·(x-0.75)·x², where 0 is a double root.
+        let factors = [[1.5, 1.0], [-0.25, 1.0], [-0.75, 1.0], [0.0, 1.0], [0.0, 1.0]].map( |f| Polynomial::new( f.map( T::from_num ) ) );
+        let product:Polynomial<T, 3> = factors[0].mul( &factors[1] );
+        let product:Polynomial<T, 4> = product.mul( &factors[2] );
+        let product:Polynomial<T, 5> = product.mul( &factors[3] );
+        let product:Polynomial<T, 6> = product.mul( &factors[4] );
+        assert_eq!{ product.degree(), 5 };
+
+        // Near a root with a small slope, the polynomial rounds to zero over a few steps of resolution.
+        let mut roots = [T::from_num(0); 5];
+        let count = product.real_roots( T::from_num(-2), T::from_num(2), &mut roots );
+        assert_eq!{ count, 4 };
+        for (root, expected) in roots.iter().zip( [-1.5, 0.0, 0.25, 0.75] ) {
+            assert!{ (root.to_num::<f64>()-expected).abs() < 0.00001 };
+        }
+
+        // Only the roots within the interval, and only as many as there is room for.
+        let count = product.real_roots( T::from_num(0.5), T::from_num(2), &mut roots );
+        assert_eq!{ roots[..count], [0.75] };
+        let count = product.real_roots( T::from_num(-2), T::from_num(2), &mut roots[..1] );
+        assert_eq!{ count, 1 };
+
+        // The whole range of the type, which is wider than the type can represent.
+        let count = product.real_roots( T::MIN, T::MAX, &mut roots );
+        assert_eq!{ count, 4 };
+        assert!{ (roots[0].to_num::<f64>()+1.5).abs() < 0.00001 };
+
+        // A root between grid points is bisected to the resolution of the type.
+        let p = Polynomial::new( [-2.0, 0.0, 1.0].map( T::from_num ) );
+        let count = p.real_roots( T::from_num(0), T::from_num(2), &mut roots );
+        assert_eq!{ count, 1 };
+        assert!{ (roots[0]-T::SQRT_2).abs() <= T::DELTA };
+    }
+}
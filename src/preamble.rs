@@ -0,0 +1,336 @@
+use fixed::traits::FixedSigned;
+use num::complex::Complex;
+use crate::matrix::wide_magnitude;
+use crate::utility::{wide_bits, from_wide_bits};
+
+/// A preamble found by normalized correlation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Detection<T> {
+    /// The position of the first sample of the preamble, relative to the first sample of the block.
+    /// It is negative when the preamble started in an earlier block.
+    pub index: isize,
+    /// The normalized correlation, `|Σ p*[k]·x[k]| / sqrt(Σ|p[k]|²·Σ|x[k]|²)`, between zero and one.
+    pub peak: T,
+    /// The coarse frequency offset in radians per sample, from the phase change between the correlations of the two halves of the preamble.
+    /// Unambiguous within ±2π divided by the length of the preamble.
+    pub frequency: T,
+    /// The phase of the correlation, which is the carrier phase at the middle of the preamble.
+    pub phase: T,
+}
+
+/// The argument of a complex number of wide bits, normalized to the range of the type before the arctangent.
+fn wide_arg<T>( re:i128, im:i128 ) -> T
+    where T: FixedSigned
+{
+    let bits = 128-re.unsigned_abs().max( im.unsigned_abs() ).leading_zeros() as i32;
+    let shift = bits-( (T::INT_NBITS+T::FRAC_NBITS) as i32-2 );
+    let (re, im) = match shift {
+        0.. => ( re >> shift, im >> shift ),
+        _   => ( re << -shift, im << -shift ),
+    };
+    return crate::utility::atan2_fixed( from_wide_bits( im ), from_wide_bits( re ) );
+}
+
+/// Correlate a window of samples with a preamble of the same length, accumulating in wide integers.
+///
+/// The index of the detection is zero. The peak is zero if either has no energy.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::preamble;
+///
+/// let value = |re:f32, im:f32| Complex::new( FixedI32::<U20>::from_num(re), FixedI32::<U20>::from_num(im) );
+/// let preamble = [value(1.0, 0.0), value(-1.0, 0.0), value(1.0, 0.0), value(1.0, 0.0)];
+/// // The preamble at half the amplitude, rotated by a quarter turn.
+/// let window = [value(0.0, 0.5), value(0.0, -0.5), value(0.0, 0.5), value(0.0, 0.5)];
+/// let detection = preamble::correlate( &window, &preamble );
+/// assert_eq!{ detection.peak, 1 };
+/// assert_eq!{ detection.frequency, 0 };
+/// assert!{ (detection.phase.to_num::<f32>()-core::f32::consts::FRAC_PI_2).abs() < 0.0001 };
+/// ```
+pub fn correlate<T>( window:&[Complex<T>], preamble:&[Complex<T>] ) -> Detection<T>
+    where T: FixedSigned
+{
+    assert_eq!{ window.len(), preamble.len() };
+    let half = preamble.len()/2;
+    // Parts of more than 62 bits are scaled down, so their products and the sums of two products fit.
+    // The peak is a ratio, and the phases are angles, so the scaling cancels.
+    let shift = (T::INT_NBITS+T::FRAC_NBITS).saturating_sub(62);
+    let parts = |item:&Complex<T>| ( wide_bits(item.re) >> shift, wide_bits(item.im) >> shift );
+    // The correlations of the two halves, and the energies.
+    let mut sums = [(0i128, 0i128); 2];
+    let (mut window_energy, mut preamble_energy) = (0i128, 0i128);
+    for (n, (x, p)) in window.iter().zip( preamble.iter() ).enumerate() {
+        let ((x_re, x_im), (p_re, p_im)) = (parts(x), parts(p));
+        let sum = &mut sums[ usize::from( half <= n ) ];
+        sum.0 = sum.0.saturating_add( p_re*x_re+p_im*x_im );
+        sum.1 = sum.1.saturating_add( p_re*x_im-p_im*x_re );
+        window_energy = window_energy.saturating_add( x_re*x_re+x_im*x_im );
+        preamble_energy = preamble_energy.saturating_add( p_re*p_re+p_im*p_im );
+    }
+
+    // Scale the sums to the resolution of the type.
+    let frac = T::FRAC_NBITS;
+    let (first, second) = (( sums[0].0 >> frac, sums[0].1 >> frac ), ( sums[1].0 >> frac, sums[1].1 >> frac ));
+    let (re, im) = ( first.0.saturating_add( second.0 ), first.1.saturating_add( second.1 ) );
+    let magnitude = wide_magnitude( re, im );
+    let norm = (((window_energy as u128).isqrt()*(preamble_energy as u128).isqrt()) >> frac) as i128;
+    let peak = match norm {
+        0 => T::from_num(0),
+        _ => from_wide_bits( magnitude.saturating_mul( 1 << frac )/norm ),
+    };
+
+    // The phase change from the first half to the second, over the distance between their centres.
+    // Only the angles matter, so each half is scaled down to 62 bits before the product.
+    let reduce = |(re, im):(i128, i128)| {
+        let shift = (128-re.unsigned_abs().max( im.unsigned_abs() ).leading_zeros()).saturating_sub(62);
+        return ( re >> shift, im >> shift );
+    };
+    let (first, second) = (reduce( first ), reduce( second ));
+    let change = wide_arg::<T>( first.0*second.0+first.1*second.1, first.0*second.1-first.1*second.0 );
+    let frequency = match preamble.len() {
+        0 | 1  => T::from_num(0),
+        length => change/(T::from_num(length)/T::from_num(2)),
+    };
+    return Detection { index: 0, peak, frequency, phase: wide_arg( re, im ) };
+}
+
+/// This macro implements a preamble detector for complex arrays, which correlates the input with a known preamble, normalized by the energy of the input.
+///
+/// A detection is reported at a local maximum of the normalized correlation which reaches the threshold, see `preamble::Detection`.
+/// To find the maximum, a detection is reported one sample after the end of the preamble, and the end of the next preamble is looked for at least a preamble length later.
+/// The last samples of each block are kept, so preambles straddling blocks are found.
+///
+/// The correlation takes a number of operations proportional to the length of the preamble for every sample.
+///
+/// ## Arguments
+/// * `name`         - The name of the detector type. E.g. PreambleDetector.
+/// * `complex_arr`  - The name of the complex array type to search. E.g. CArr32.
+/// * `preamble_arr` - The name of the complex array type of the preamble. E.g. CArr16.
+/// * `T`            - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate a detector for a preamble, with a threshold of the normalized correlation between zero and one. E.g. 0.8.
+///
+/// # `::push`
+/// Push a sample, and return a detection if the preamble ended with the previous sample. The index is relative to the pushed sample.
+///
+/// # `::process`
+/// Search a block of samples, write the detections into a slice, and return their number. Detections beyond the length of the slice are dropped.
+///
+/// # `::reset`
+/// Forget the kept samples and any pending detection.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::preamble::Detection;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+///
+/// ia::declare_array_complex!( CArr16, Arr16, 16, FixedI32<U20> );
+/// ia::declare_array_complex!( CArr8, Arr8, 8, FixedI32<U20> );
+/// ia::declare_preamble_detector!( PreambleDetector, CArr16, CArr8, FixedI32<U20> );
+///
+/// let bits = [1.0, 1.0, 1.0, -1.0, -1.0, 1.0, -1.0, 1.0];
+/// let mut preamble = CArr8::new_from_i32( 0, 0 );
+/// for (item, bit) in preamble.data.iter_mut().zip( bits ) {
+///     item.re = FixedI32::<U20>::from_num(bit);
+/// }
+/// let mut detector = PreambleDetector::new( &preamble, FixedI32::<U20>::from_num(0.9) );
+///
+/// // The preamble starts at sample 12 of the first block, and ends in the second.
+/// let mut blocks = [CArr16::new_from_i32( 0, 0 ); 2];
+/// for (n, item) in preamble.data.iter().enumerate() {
+///     blocks[(12+n)/16].data[(12+n)%16] = *item;
+/// }
+/// let mut detections = [Detection { index: 0, peak: FixedI32::<U20>::from_num(0), frequency: FixedI32::<U20>::from_num(0), phase: FixedI32::<U20>::from_num(0) }; 2];
+/// assert_eq!{ detector.process( &blocks[0], &mut detections ), 0 };
+/// assert_eq!{ detector.process( &blocks[1], &mut detections ), 1 };
+/// assert_eq!{ detections[0].index, -4 };
+/// assert_eq!{ detections[0].peak, 1 };
+/// ```
+#[macro_export]
+macro_rules! declare_preamble_detector{
+    ( $name:ident, $complex_arr:ident, $preamble_arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// A preamble detector.
+        pub struct $name{
+            preamble: $preamble_arr,
+            threshold: $T,
+            // The most recent samples, the oldest first.
+            history: $preamble_arr,
+            filled: usize,
+            previous_peak: $T,
+            pending: Option<integer_array::preamble::Detection<$T>>,
+            holdoff: usize,
+        }
+
+        impl $name {
+            /// Generate a detector for a preamble.
+            #[allow(dead_code)]
+            fn new( preamble:&$preamble_arr, threshold:$T ) -> Self {
+                let mut history = *preamble;
+                history.data.fill( num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ) );
+                $name {
+                    preamble: *preamble,
+                    threshold,
+                    history,
+                    filled: 0,
+                    previous_peak: <$T>::from_num(0),
+                    pending: None,
+                    holdoff: 0,
+                }
+            }
+            /// Forget the kept samples and any pending detection.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.history.data.fill( num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ) );
+                self.filled = 0;
+                self.previous_peak = <$T>::from_num(0);
+                self.pending = None;
+                self.holdoff = 0;
+            }
+            /// Push a sample, and return a detection if the preamble ended with the previous sample.
+            #[allow(dead_code)]
+            fn push( &mut self, item:num::complex::Complex<$T> ) -> Option<integer_array::preamble::Detection<$T>> {
+                let length = self.history.data.len();
+                self.history.data.copy_within( 1..length, 0 );
+                self.history.data[length-1] = item;
+                self.filled = length.min( self.filled+1 );
+
+                let mut detection = integer_array::preamble::correlate( &self.history.data, &self.preamble.data );
+                detection.index = -(length as isize);
+                let mut r_detection = None;
+                if let Some(pending) = self.pending.take()
+                {
+                    if detection.peak <= pending.peak
+                    {
+                        r_detection = Some( pending );
+                        self.holdoff = length-1;
+                    }
+                }
+                if 0 < self.holdoff
+                {
+                    self.holdoff -= 1;
+                }
+                else if self.filled == length && self.threshold <= detection.peak && self.previous_peak <= detection.peak
+                {
+                    self.pending = Some( detection );
+                }
+                self.previous_peak = detection.peak;
+                return r_detection;
+            }
+            /// Search a block of samples, write the detections into a slice, and return their number.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$complex_arr, detections:&mut [integer_array::preamble::Detection<$T>] ) -> usize {
+                let mut count = 0;
+                for (n, item) in input.data.iter().enumerate() {
+                    if let Some(mut detection) = self.push( *item )
+                    {
+                        if count < detections.len()
+                        {
+                            detection.index += n as isize;
+                            detections[count] = detection;
+                            count += 1;
+                        }
+                    }
+                }
+                return count;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use super::Detection;
+    use fixed::{types::extra::U20, FixedI32};
+    use num::complex::Complex;
+
+    type T = FixedI32<U20>;
+
+    #[test]
+    fn bursts_are_found_across_blocks() {
+        integer_array::declare_array_complex!( CArr32, Arr32, 32, T );
+        integer_array::declare_array_complex!( CArr16, Arr16, 16, T );
+        integer_array::declare_preamble_detector!( PreambleDetector, CArr32, CArr16, T );
+
+        // A pseudo-random BPSK preamble, and pseudo-random QPSK data around it.
+        let mut seed = 11u32;
+        let mut random = || {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            return ( if seed >> 16 & 1 == 0 { 1.0 } else { -1.0 }, if seed >> 17 & 1 == 0 { 1.0 } else { -1.0 } );
+        };
+        let bits:[f64; 16] = core::array::from_fn( |_| random().0 );
+        let preamble = CArr16 { data: bits.map( |bit| Complex::new( T::from_num(bit), T::from_num(0) ) ) };
+
+        // Two bursts at a tenth of the amplitude, with a frequency offset of 0.05 radians per sample and a phase of 1 radian.
+        let starts = [40, 86];
+        let signal:[(f64, f64); 160] = core::array::from_fn( |n| {
+            let symbol = match starts.iter().find( |start| (**start..**start+16).contains( &n ) ) {
+                Some(start) => ( bits[n-start], 0.0 ),
+                None        => random(),
+            };
+            let (sin, cos) = (0.05*n as f64+1.0).sin_cos();
+            return ( 0.1*(symbol.0*cos-symbol.1*sin), 0.1*(symbol.0*sin+symbol.1*cos) );
+        } );
+
+        let mut detector = PreambleDetector::new( &preamble, T::from_num(0.8) );
+        let zero = T::from_num(0);
+        let mut found = [Detection { index: 0, peak: zero, frequency: zero, phase: zero }; 4];
+        let mut count = 0;
+        for block in 0..5 {
+            let input = CArr32 { data: core::array::from_fn( |n| {
+                let (re, im) = signal[32*block+n];
+                Complex::new( T::from_num(re), T::from_num(im) )
+            } ) };
+            let mut detections = [Detection { index: 0, peak: zero, frequency: zero, phase: zero }; 2];
+            let block_count = detector.process( &input, &mut detections );
+            for mut detection in detections[..block_count].iter().copied() {
+                detection.index += 32*block as isize;
+                found[count] = detection;
+                count += 1;
+            }
+        }
+        assert_eq!{ count, 2 };
+        for (detection, start) in found.iter().zip( starts ) {
+            assert_eq!{ detection.index, start as isize };
+            assert!{ 0.95 < detection.peak };
+            assert!{ (detection.frequency.to_num::<f64>()-0.05).abs() < 0.005 };
+            // The phase at the middle of the preamble.
+            let phase = (0.05*(start as f64+7.5)+1.0+core::f64::consts::PI).rem_euclid( 2.0*core::f64::consts::PI )-core::f64::consts::PI;
+            assert!{ (detection.phase.to_num::<f64>()-phase).abs() < 0.01 };
+        }
+
+        // Noise alone is not detected.
+        detector.reset();
+        let input = CArr32 { data: core::array::from_fn( |_| {
+            let (re, im) = random();
+            Complex::new( T::from_num(re), T::from_num(im) )
+        } ) };
+        assert_eq!{ detector.process( &input, &mut found ), 0 };
+    }
+
+    #[test]
+    fn correlate_handles_large_64_bit_items() {
+        type W = fixed::types::I32F32;
+        // Items near the top of the range, whose products overflow i128 unless scaled down.
+        let amplitude = 2.0e9;
+        let preamble:[Complex<W>; 8] = core::array::from_fn( |n| {
+            let sign = if n%3 == 0 { -1.0 } else { 1.0 };
+            Complex::new( W::from_num(sign*amplitude), W::from_num(-sign*amplitude) )
+        } );
+        let detection = super::correlate( &preamble, &preamble );
+        assert!{ (detection.peak.to_num::<f64>()-1.0).abs() < 0.001 };
+        assert!{ detection.frequency.to_num::<f64>().abs() < 0.001 };
+        assert!{ detection.phase.to_num::<f64>().abs() < 0.001 };
+    }
+}
@@ -1,5 +1,3 @@
-use fixed_trigonometry;
-
 /// This macro implements a type which consists of an array of fixed-point numberts of size N.
 /// Complete with the traits shown below.
 /// 
@@ -319,7 +317,6 @@ use fixed_trigonometry;
 /// use integer_array as ia;
 /// use ia::trait_definitions::*;
 /// use fixed::{types::extra::U20, FixedI32};
-
 /// 
 /// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
 /// let mut x = Arr4::ramp_from_f32(10.0,22.0);
@@ -409,7 +406,6 @@ use fixed_trigonometry;
 /// use integer_array as ia;
 /// use ia::trait_definitions::*;
 /// use fixed::{types::extra::U20, FixedI32};
-
 /// 
 /// ia::declare_array_real!( Arr8, 8, FixedI32<U20> );
 /// let mut x = Arr8::ramp_from_f32(0.0,60.0);
@@ -423,6 +419,26 @@ use fixed_trigonometry;
 /// With the first-quarter method, the resulting cosine power spectrum is displayed below.
 /// ![Alt version](https://github.com/ErikBuer/Integer-Array/blob/main/numerical_verificatons/figures/cos/frequency_domain2.png?raw=true)
 /// 
+/// # `::sin_table` and `::cos_table`
+/// Take the element-wise sine or cosine through a linearly interpolated quarter-wave table, generated at compile time.
+/// An alternative to the Taylor approximations above, trading read-only memory for speed.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// ia::declare_trig_table!( Trig256, 256, FixedI32<U20> );
+/// let x = Arr4::new_from_f32_array( [0.0, 3.1415927/6.0, 3.1415927/2.0, 7.0] );
+/// let y = x.sin_table::<Trig256>();
+/// assert_eq!{ y.as_array_f32(), [0.0, 0.49999714, 0.99999905, 0.6569834] };
+/// let y = x.cos_table::<Trig256>();
+/// assert_eq!{ y.as_array_f32(), [0.99999905, 0.8660221, -9.536743e-7, 0.7539005] };
+/// ```
+/// 
 /// 
 /// 
 /// # `::tan`
@@ -432,6 +448,7 @@ use fixed_trigonometry;
 /// 
 /// `tan(x) = x+( x^3/3 )+( x^5*2/15.0 )+( x^7*17/315.0 )+( x^9*62/2835.0 )+( x^11*1382/155925.0 )+( x^13*21844/6081075.0 )+( x^15*929569/638512875.0 )`
 /// 
+/// The polynomial is evaluated in x² by Horner's method, with intermediate values in I16F48, so the high-order terms keep their precision in types with few fractional bits.
 /// Self must be wrapped to the -π/2=<x<π/2 range.
 /// The function is based on a Taylor expansion. Its error increases as |x| approaches π/2.
 /// 
@@ -445,7 +462,7 @@ use fixed_trigonometry;
 /// ia::declare_array_real!( Arr8, 8, FixedI32<U4> );
 /// let x = Arr8::ramp_from_f32(0.0,0.17);
 /// let y = x.tan();
-/// assert_eq!{ y.as_array_f32(), [0.0, 0.1875, 0.375, 0.5625, 0.875, 1.25, 1.6875, 2.5625] };
+/// assert_eq!{ y.as_array_f32(), [0.0, 0.1875, 0.375, 0.625, 0.875, 1.3125, 2.0625, 3.5625] };
 /// ```
 /// 
 /// Below is the the taylor approximation for tan compared to the Julia native tan function.
@@ -485,7 +502,6 @@ use fixed_trigonometry;
 /// use integer_array as ia;
 /// use ia::trait_definitions::*;
 /// use fixed::{types::extra::U20, FixedI32};
-
 /// 
 /// ia::declare_array_real!( Arr32, 32, FixedI32<U20> );
 /// let x = Arr32::ramp_from_f32(100.0,20.0);
@@ -495,7 +511,6 @@ use fixed_trigonometry;
 /// use integer_array as ia;
 /// use ia::trait_definitions::*;
 /// use fixed::{types::extra::U20, FixedI32};
-
 /// 
 /// ia::declare_array_real!( Arr32, 32, FixedI32<U20> );
 /// let x = Arr32::ramp_from_f32(100.0,20.0);
@@ -526,16 +541,208 @@ use fixed_trigonometry;
 /// let x = Arr32::ramp_from_f32(100.0,20.0);
 /// assert_eq!{x.argmin(), 0};
 /// ```
+/// 
+/// # `::mean`, `::var`, `::std_dev`, `::rms` and `::energy`
+/// The moments of the array are accumulated in wide integers, so the squares do not overflow the type.
+/// The variance is the population variance.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// let x = Arr4::new_from_f32_array( [10.0, 30.0, -30.0, 50.0] );
+/// assert_eq!{ x.mean(), 15 };
+/// // The squared deviations add up to 3500, beyond the range of the type, but their mean fits.
+/// assert_eq!{ x.var(), 875 };
+/// assert_eq!{ x.std_dev().to_num::<f32>(), 29.580398 };
+/// assert_eq!{ x.rms().to_num::<f32>(), 33.166248 };
+/// // The energy of 4400 saturates the type.
+/// assert_eq!{ x.energy(), FixedI32::<U20>::MAX };
+/// ```
+/// 
+/// # `::median` and `::percentile`
+/// The median is found by quickselect, see `::nth_element`.
+/// The percentile is given in the 0..=100 range, and is interpolated linearly between the sorted items.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// let x = Arr4::new_from_f32_array( [4.0, -1.0, 3.0, 0.0] );
+/// assert_eq!{ x.median(), 1.5 };
+/// assert_eq!{ x.percentile( FixedI32::<U20>::from_num(0) ), -1 };
+/// assert_eq!{ x.percentile( FixedI32::<U20>::from_num(50) ), 1.5 };
+/// assert_eq!{ x.percentile( FixedI32::<U20>::from_num(100) ), 4 };
+/// ```
+/// 
+/// # `::sort`, `::sort_descending`, `::sorted` and `::sorted_descending`
+/// Sort the array in place, or return a sorted copy of it.
+/// 
+/// # `::argsort` and `::nth_element`
+/// Get the indices that sort the array in ascending order, or the n-th smallest item, found by quickselect.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// let mut x = Arr4::new_from_f32_array( [4.0, -1.0, 3.0, 0.0] );
+/// assert_eq!{ x.sorted_descending().as_array_i32(), [4, 3, 0, -1] };
+/// assert_eq!{ x.argsort(), [1, 3, 2, 0] };
+/// assert_eq!{ x.nth_element( 2 ), 3 };
+/// x.sort();
+/// assert_eq!{ x.as_array_i32(), [-1, 0, 3, 4] };
+/// ```
+/// 
+/// # `::histogram` and `::histogram_edges`
+/// Count the items in B uniform bins, or in the bins between B+1 ascending edges. Items outside the bins are not counted.
+/// See the `histogram` module for the cumulative distribution and quantiles of the counts.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr6, 6, FixedI32<U20> );
+/// let x = Arr6::new_from_f32_array( [0.1, 0.9, 0.4, -0.2, 0.6, 2.0] );
+/// let counts = x.histogram::<4>( FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(1) );
+/// assert_eq!{ counts, [1, 1, 1, 1] };
+/// 
+/// let edges = [FixedI32::<U20>::from_num(-1), FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(2)];
+/// assert_eq!{ x.histogram_edges::<2>( &edges ), [1, 5] };
+/// ```
+/// 
+/// # `::skewness` and `::kurtosis`
+/// The standardized third moment, and the excess kurtosis, the standardized fourth moment less three.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// let x = Arr4::new_from_f32_array( [0.0, 0.0, 0.0, 1.0] );
+/// assert_eq!{ x.skewness().to_num::<f32>(), 1.1547022 };
+/// assert_eq!{ x.kurtosis().to_num::<f32>(), -0.666667 };
+/// ```
+/// 
+/// # `::peak_to_average`, `::crest_factor` and `::zero_crossings`
+/// The peak-to-average power ratio `max(x²)/E[x²]`, the crest factor `max(|x|)/rms`, and the number of sign changes.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr4, 4, FixedI32<U20> );
+/// let x = Arr4::new_from_f32_array( [1.0, -1.0, -1.0, 3.0] );
+/// assert_eq!{ x.peak_to_average(), 3 };
+/// assert_eq!{ x.crest_factor().to_num::<f32>(), 1.7320509 };
+/// assert_eq!{ x.zero_crossings(), 2 };
+/// ```
+/// 
+/// # `::interpolate_linear`, `::interpolate_cubic` and `::interpolate_lagrange`
+/// Look up the array at a fractional index, by linear, cubic Hermite, or Lagrange interpolation of a given order.
+/// Indices beyond the ends take the end items. See the `interpolation` module.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr5, 5, FixedI32<U20> );
+/// let x = Arr5::new_from_f32_array( [0.0, 1.0, 4.0, 9.0, 16.0] );
+/// let index = FixedI32::<U20>::from_num(1.5);
+/// assert_eq!{ x.interpolate_linear( index ), 2.5 };
+/// assert_eq!{ x.interpolate_cubic( index ), 2.25 };
+/// assert_eq!{ x.interpolate_lagrange( index, 2 ), 2.25 };
+/// ```
+/// 
+/// # `::window`
+/// Generate a symmetric window function of the array length.
+/// 
+/// ## Arguments
+/// 
+/// * `window` - The window function, see `integer_array::window::Window`.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::window::Window;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr5, 5, FixedI32<U20> );
+/// let w = Arr5::window( Window::Hann );
+/// assert_eq!{ w.as_array_f32(), [0.0, 0.5, 1.0, 0.5, 0.0] };
+/// ```
+/// 
+/// # FIR filter design
+/// The array can be initialized as the taps of an FIR filter.
+/// All frequencies are relative to the sample rate.
+/// 
+/// * `::lowpass( cutoff, window )` - Windowed-sinc lowpass, with unity gain at DC.
+/// * `::highpass( cutoff, window )` - Windowed-sinc highpass, with unity gain at the Nyquist frequency. The length must be odd.
+/// * `::bandpass( low, high, window )` - Windowed-sinc bandpass, with unity gain at the center of the passband.
+/// * `::bandstop( low, high, window )` - Windowed-sinc bandstop, with unity gain at DC. The length must be odd.
+/// * `::raised_cosine( rolloff, samples_per_symbol )` - Raised-cosine pulse with a center tap of one.
+/// * `::root_raised_cosine( rolloff, samples_per_symbol )` - Root-raised-cosine pulse with unit energy.
+/// 
+/// The gain of the windowed-sinc filters is normalized using the quantized taps, see `integer_array::filter::design`.
+/// 
+/// ## Example
+/// 
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::window::Window;
+/// use fixed::{types::extra::U20, FixedI32};
+/// 
+/// ia::declare_array_real!( Arr15, 15, FixedI32<U20> );
+/// let h = Arr15::lowpass( FixedI32::<U20>::from_num(0.1), Window::Hamming );
+/// assert_eq!{ h.sum(), 1 };
+/// 
+/// let h = Arr15::raised_cosine( FixedI32::<U20>::from_num(0.5), 4 );
+/// assert_eq!{ h[7], 1 };
+/// assert_eq!{ h[3], 0 };
+/// ```
 #[macro_export]
 macro_rules! declare_array_real{
     ( $name:ident, $N:expr, $T:ty ) => {
 
-        #[derive(Copy, Clone, Default, Debug, PartialEq)]
+        #[derive(Copy, Clone, Debug, PartialEq)]
         /// Real numeric array of type int32.
         pub struct $name{
             pub data: [$T; $N],
         }
 
+        // Implemented by hand, as arrays longer than 32 items do not implement Default.
+        impl Default for $name {
+            fn default() -> Self {
+                return $name::new_from_i32(0);
+            }
+        }
+
         impl $name {
             /// Generate an array of a value.
             #[allow(dead_code)]
@@ -630,6 +837,58 @@ macro_rules! declare_array_real{
             }
         }
 
+        impl $name {
+            /// Generate a symmetric window function.
+            #[allow(dead_code)]
+            fn window( window: integer_array::window::Window ) -> Self {
+                let mut r_array = $name::new_from_i32(0);
+                integer_array::window::fill( window, &mut r_array.data );
+                return r_array;
+            }
+            /// Generate the taps of a windowed-sinc lowpass filter.
+            #[allow(dead_code)]
+            fn lowpass( cutoff: $T, window: integer_array::window::Window ) -> Self {
+                let mut r_array = $name::new_from_i32(0);
+                integer_array::filter::design::lowpass( &mut r_array.data, cutoff, window );
+                return r_array;
+            }
+            /// Generate the taps of a windowed-sinc highpass filter.
+            #[allow(dead_code)]
+            fn highpass( cutoff: $T, window: integer_array::window::Window ) -> Self {
+                let mut r_array = $name::new_from_i32(0);
+                integer_array::filter::design::highpass( &mut r_array.data, cutoff, window );
+                return r_array;
+            }
+            /// Generate the taps of a windowed-sinc bandpass filter.
+            #[allow(dead_code)]
+            fn bandpass( low: $T, high: $T, window: integer_array::window::Window ) -> Self {
+                let mut r_array = $name::new_from_i32(0);
+                integer_array::filter::design::bandpass( &mut r_array.data, low, high, window );
+                return r_array;
+            }
+            /// Generate the taps of a windowed-sinc bandstop filter.
+            #[allow(dead_code)]
+            fn bandstop( low: $T, high: $T, window: integer_array::window::Window ) -> Self {
+                let mut r_array = $name::new_from_i32(0);
+                integer_array::filter::design::bandstop( &mut r_array.data, low, high, window );
+                return r_array;
+            }
+            /// Generate the taps of a raised-cosine pulse shaping filter.
+            #[allow(dead_code)]
+            fn raised_cosine( rolloff: $T, samples_per_symbol: usize ) -> Self {
+                let mut r_array = $name::new_from_i32(0);
+                integer_array::filter::design::raised_cosine( &mut r_array.data, rolloff, samples_per_symbol );
+                return r_array;
+            }
+            /// Generate the taps of a root-raised-cosine pulse shaping filter.
+            #[allow(dead_code)]
+            fn root_raised_cosine( rolloff: $T, samples_per_symbol: usize ) -> Self {
+                let mut r_array = $name::new_from_i32(0);
+                integer_array::filter::design::root_raised_cosine( &mut r_array.data, rolloff, samples_per_symbol );
+                return r_array;
+            }
+        }
+
         impl integer_array::trait_definitions::Initializers for $name {
             /// Generate an array of ones.
             fn ones() -> Self {
@@ -1014,28 +1273,66 @@ macro_rules! declare_array_real{
             fn sum( &self ) -> $T {
                 let mut sum:$T = <$T>::from_num(0);
                 for index in 0..$N {
-                    sum = sum + self.data[index];
+                    sum += self.data[index];
                 }
                 return sum;
             }
-            /// Return the mean of the array.
+            /// Return the mean of the array, accumulated in a wide integer.
             #[allow(dead_code)]
             fn mean( &self ) -> $T {
-                let mut sum:$T = <$T>::from_num(0);
-                for index in 0..$N {
-                    sum = sum + self.data[index];
-                }
-                return sum/$N;
+                return integer_array::utility::from_wide_bits::<$T>( self.mean_bits() );
             }
-            /// Return the variance of the array.
+            /// Return the variance of the array, accumulated in a wide integer.
             #[allow(dead_code)]
             fn var( &self ) -> $T {
-                let mean = self.mean();
-                let mut temp: $T = <$T>::from_num(0);
-                for idx in 0..$N {
-                    temp = temp + integer_array::utility::fixed_powi((self[idx]-mean), 2);
+                return integer_array::utility::from_wide_bits::<$T>( self.central_moment_bits(2) >> <$T>::FRAC_NBITS );
+            }
+            /// The mean, in bits of the type.
+            #[allow(dead_code)]
+            fn mean_bits( &self ) -> i128 {
+                let mut sum:i128 = 0;
+                for item in self.data.iter() {
+                    sum += item.to_bits() as i128;
+                }
+                return sum/($N as i128);
+            }
+            /// The mean square, with twice the fractional bits of the type.
+            #[allow(dead_code)]
+            fn mean_square_bits( &self ) -> i128 {
+                let mut sum:i128 = 0;
+                for item in self.data.iter() {
+                    sum += (item.to_bits() as i128)*(item.to_bits() as i128);
                 }
-                return temp/$N;
+                return sum/($N as i128);
+            }
+            /// The central moment of order 2 or 3, with that many times the fractional bits of the type.
+            #[allow(dead_code)]
+            fn central_moment_bits( &self, order:u32 ) -> i128 {
+                let mean = self.mean_bits();
+                let mut sum:i128 = 0;
+                for item in self.data.iter() {
+                    let deviation = item.to_bits() as i128-mean;
+                    sum += match order {
+                        2 => deviation*deviation,
+                        _ => deviation*deviation*deviation,
+                    };
+                }
+                return sum/($N as i128);
+            }
+            /// Return the standard deviation of the array.
+            #[allow(dead_code)]
+            fn std_dev( &self ) -> $T {
+                return integer_array::utility::from_wide_bits::<$T>( (self.central_moment_bits(2) as u128).isqrt() as i128 );
+            }
+            /// Return the root mean square of the array.
+            #[allow(dead_code)]
+            fn rms( &self ) -> $T {
+                return integer_array::utility::from_wide_bits::<$T>( (self.mean_square_bits() as u128).isqrt() as i128 );
+            }
+            /// Return the energy of the array, the sum of the squared items.
+            #[allow(dead_code)]
+            fn energy( &self ) -> $T {
+                return integer_array::utility::from_wide_bits::<$T>( (self.mean_square_bits()*($N as i128)) >> <$T>::FRAC_NBITS );
             }
             /// Return the higherst value in the array.
             #[allow(dead_code)]
@@ -1089,6 +1386,204 @@ macro_rules! declare_array_real{
                 } 
                 return arg_min;
             }
+            /// Return the median of the array, the mean of the two middle items for even lengths.
+            #[allow(dead_code)]
+            fn median( &self ) -> $T {
+                let mut copy = self.data;
+                let (left, upper, _) = copy.select_nth_unstable( $N/2 );
+                if $N%2 == 1
+                {
+                    return *upper;
+                }
+                // The lower middle item is the greatest of the items before the upper one.
+                let lower = left.iter().max().unwrap_or( upper );
+                return integer_array::utility::from_wide_bits::<$T>( (lower.to_bits() as i128+upper.to_bits() as i128)/2 );
+            }
+            /// Return the percentile of the array, in the 0..=100 range, interpolated linearly between the items.
+            #[allow(dead_code)]
+            fn percentile( &self, percent:$T ) -> $T {
+                let mut sorted = self.data;
+                sorted.sort_unstable();
+                let percent = percent.max( <$T>::from_num(0) ).min( <$T>::from_num(100) );
+                // The position in the sorted array, with the fractional bits of the type.
+                let position = (percent.to_bits() as i128)*(($N-1) as i128)/100;
+                let index = (position >> <$T>::FRAC_NBITS) as usize;
+                if $N-1 <= index
+                {
+                    return sorted[$N-1];
+                }
+                let fraction = position & ((1i128 << <$T>::FRAC_NBITS)-1);
+                let lower = sorted[index].to_bits() as i128;
+                let upper = sorted[index+1].to_bits() as i128;
+                return integer_array::utility::from_wide_bits::<$T>( lower+(((upper-lower)*fraction) >> <$T>::FRAC_NBITS) );
+            }
+            /// Return the skewness of the array, `E[(x-μ)³]/σ³`.
+            /// Returns zero for constant arrays.
+            #[allow(dead_code)]
+            fn skewness( &self ) -> $T {
+                let variance = self.central_moment_bits(2);
+                // σ³, with three times the fractional bits of the type.
+                let cube = variance*((variance as u128).isqrt() as i128);
+                if cube == 0
+                {
+                    return <$T>::from_num(0);
+                }
+                return integer_array::utility::from_wide_bits::<$T>( (self.central_moment_bits(3) << <$T>::FRAC_NBITS)/cube );
+            }
+            /// Return the excess kurtosis of the array, `E[(x-μ)⁴]/σ⁴-3`, which is zero for a normal distribution.
+            /// Returns zero for constant arrays.
+            #[allow(dead_code)]
+            fn kurtosis( &self ) -> $T {
+                // The kurtosis is calculated as E[r²]-3 with `r = d²/E[d²]`, in 32 fractional bits.
+                // The deviations d are scaled down so that N·d² with the 32 fractional bits fits. The kurtosis is a ratio, so the scaling cancels.
+                let mean = self.mean_bits();
+                let length_bits = 128-($N as u128).leading_zeros();
+                let largest = self.data.iter().map( |item| (item.to_bits() as i128-mean).unsigned_abs() ).max().unwrap_or(0);
+                let shift = (128-largest.leading_zeros()).saturating_sub( (94-length_bits)/2 );
+                let squares = self.data.map( |item| ((item.to_bits() as i128-mean) >> shift).pow(2) );
+                let sum_squares:i128 = squares.iter().sum();
+                if sum_squares == 0
+                {
+                    return <$T>::from_num(0);
+                }
+                let mut sum:i128 = 0;
+                for square in squares.iter() {
+                    let ratio = ((square*($N as i128)) << 32)/sum_squares;
+                    sum += (ratio*ratio) >> 32;
+                }
+                let kurtosis = sum/($N as i128)-(3i128 << 32);
+                // Rounded to the fractional bits of the type.
+                let bits = match <$T>::FRAC_NBITS {
+                    frac if frac < 32 => (kurtosis+(1i128 << (31-frac))) >> (32-frac),
+                    frac              => kurtosis << (frac-32),
+                };
+                return integer_array::utility::from_wide_bits::<$T>( bits );
+            }
+            /// Return the ratio of the peak power to the average power of the array, `max(x²)/E[x²]`.
+            /// Returns zero for arrays of zeros.
+            #[allow(dead_code)]
+            fn peak_to_average( &self ) -> $T {
+                let mean_square = self.mean_square_bits();
+                if mean_square == 0
+                {
+                    return <$T>::from_num(0);
+                }
+                let mut peak:i128 = 0;
+                for item in self.data.iter() {
+                    peak = peak.max( (item.to_bits() as i128)*(item.to_bits() as i128) );
+                }
+                return integer_array::utility::from_wide_bits::<$T>( (peak << <$T>::FRAC_NBITS)/mean_square );
+            }
+            /// Return the crest factor of the array, `max(|x|)/rms`.
+            /// Returns zero for arrays of zeros.
+            #[allow(dead_code)]
+            fn crest_factor( &self ) -> $T {
+                let rms = (self.mean_square_bits() as u128).isqrt() as i128;
+                if rms == 0
+                {
+                    return <$T>::from_num(0);
+                }
+                let mut peak:i128 = 0;
+                for item in self.data.iter() {
+                    peak = peak.max( (item.to_bits() as i128).abs() );
+                }
+                return integer_array::utility::from_wide_bits::<$T>( (peak << <$T>::FRAC_NBITS)/rms );
+            }
+            /// Sort the array in ascending order, in place.
+            #[allow(dead_code)]
+            fn sort( &mut self ) {
+                self.data.sort_unstable();
+            }
+            /// Sort the array in descending order, in place.
+            #[allow(dead_code)]
+            fn sort_descending( &mut self ) {
+                self.data.sort_unstable_by( |a, b| b.cmp(a) );
+            }
+            /// Return a copy of the array, sorted in ascending order.
+            #[allow(dead_code)]
+            fn sorted( &self ) -> Self {
+                let mut r_array = *self;
+                r_array.sort();
+                return r_array;
+            }
+            /// Return a copy of the array, sorted in descending order.
+            #[allow(dead_code)]
+            fn sorted_descending( &self ) -> Self {
+                let mut r_array = *self;
+                r_array.sort_descending();
+                return r_array;
+            }
+            /// Return the indices that sort the array in ascending order. Equal items keep their order.
+            #[allow(dead_code)]
+            fn argsort( &self ) -> [usize; $N] {
+                let mut indices = [0; $N];
+                for (n, index) in indices.iter_mut().enumerate() {
+                    *index = n;
+                }
+                indices.sort_unstable_by_key( |index| (self.data[*index], *index) );
+                return indices;
+            }
+            /// Return the item that would be at index `n` if the array was sorted in ascending order.
+            /// Found by quickselect on a copy, in O(N) on average.
+            #[allow(dead_code)]
+            fn nth_element( &self, n:usize ) -> $T {
+                let mut copy = self.data;
+                let (_, nth, _) = copy.select_nth_unstable( n );
+                return *nth;
+            }
+            /// Count the items in B uniform bins from `low` to `high`, see `histogram::uniform_bin`.
+            #[allow(dead_code)]
+            fn histogram<const B:usize>( &self, low:$T, high:$T ) -> [u64; B] {
+                let mut counts = [0; B];
+                for item in self.data.iter() {
+                    if let Some(bin) = integer_array::histogram::uniform_bin( *item, low, high, B )
+                    {
+                        counts[bin] += 1;
+                    }
+                }
+                return counts;
+            }
+            /// Count the items in the B bins between B+1 ascending edges, see `histogram::edge_bin`.
+            #[allow(dead_code)]
+            fn histogram_edges<const B:usize>( &self, edges:&[$T] ) -> [u64; B] {
+                assert_eq!{ edges.len(), B+1 };
+                let mut counts = [0; B];
+                for item in self.data.iter() {
+                    if let Some(bin) = integer_array::histogram::edge_bin( *item, edges )
+                    {
+                        counts[bin] += 1;
+                    }
+                }
+                return counts;
+            }
+            /// Return the linearly interpolated value at a fractional index, see `interpolation::linear`.
+            #[allow(dead_code)]
+            fn interpolate_linear( &self, index:$T ) -> $T {
+                return integer_array::interpolation::linear( &self.data, index );
+            }
+            /// Return the cubic Hermite interpolated value at a fractional index, see `interpolation::cubic_hermite`.
+            #[allow(dead_code)]
+            fn interpolate_cubic( &self, index:$T ) -> $T {
+                return integer_array::interpolation::cubic_hermite( &self.data, index );
+            }
+            /// Return the Lagrange interpolated value of an order at a fractional index, see `interpolation::lagrange`.
+            #[allow(dead_code)]
+            fn interpolate_lagrange( &self, index:$T, order:usize ) -> $T {
+                return integer_array::interpolation::lagrange( &self.data, index, order );
+            }
+            /// Return the number of sign changes between neighbouring items. Zero counts as positive.
+            #[allow(dead_code)]
+            fn zero_crossings( &self ) -> usize {
+                let zero = <$T>::from_num(0);
+                let mut crossings = 0;
+                for pair in self.data.windows(2) {
+                    if (pair[0] < zero) != (pair[1] < zero)
+                    {
+                        crossings += 1;
+                    }
+                }
+                return crossings;
+            }
         }
         
         
@@ -1133,18 +1628,43 @@ macro_rules! declare_array_real{
             }
         }
 
+        impl $name {
+            /// Take the element-wise sine through an interpolated lookup table, see `declare_trig_table`.
+            /// Self is wrapped to the -π=<x<π range by the table.
+            #[allow(dead_code)]
+            fn sin_table<L: integer_array::trait_definitions::TrigTable<$T>>( &self ) -> Self {
+                let mut r_array = *self;
+                for item in r_array.data.iter_mut() {
+                    *item = L::sin( *item );
+                }
+                return r_array;
+            }
+
+            /// Take the element-wise cosine through an interpolated lookup table, see `declare_trig_table`.
+            /// Self is wrapped to the -π=<x<π range by the table.
+            #[allow(dead_code)]
+            fn cos_table<L: integer_array::trait_definitions::TrigTable<$T>>( &self ) -> Self {
+                let mut r_array = *self;
+                for item in r_array.data.iter_mut() {
+                    *item = L::cos( *item );
+                }
+                return r_array;
+            }
+        }
+
         impl integer_array::trait_definitions::Tan for $name {
             /// Take the element-wise tan using a Taylor approximation of tan x.
             /// Self must be wrapped to the -π/2=<x<π/2 range.
             /// The function is based on a Taylor expansion. Its error increases as |x| approaches π/2.
             fn tan( &self ) -> Self {                
-                use integer_array::utility as util;
+                use fixed::types::I16F48;
+                // tan(x) = x·P(x²). P is evaluated by Horner's method in I16F48, which holds the small high-order coefficients
+                // regardless of the fractional bits of the array.
+                let polynomial = integer_array::polynomial::Polynomial::new( [1.0, 1.0/3.0, 2.0/15.0, 17.0/315.0, 62.0/2835.0, 1382.0/155925.0, 21844.0/6081075.0, 929569.0/638512875.0].map( I16F48::from_num ) );
                 let mut r_array = $name::new_from_i32(0);
                 for idx in 0..$N {
-                    let x = self[idx];
-                    // Calculate tan by using a polynomial 
-                    r_array[idx] = x+( util::fixed_powi(x,3)/3 )+( util::fixed_powi(x,5)*2/15 )-( util::fixed_powi(x,7)*17/315 )+( util::fixed_powi(x,9)*62/2835 )+( util::fixed_powi(x,11)*1382/155925 )
-                                    +( util::fixed_powi(x,13)*21844/6081075 )+( util::fixed_powi(x,15)*929569/638512875 );
+                    let x = I16F48::saturating_from_num( self[idx] );
+                    r_array[idx] = <$T>::saturating_from_num( x*polynomial.evaluate( x*x ) );
                 } 
                 return r_array;
             }
@@ -1178,6 +1698,20 @@ macro_rules! declare_array_real{
             }
         }
 
+        impl core::convert::From<[$T; $N]> for $name {
+            /// Wrap a primitive array.
+            fn from( data:[$T; $N] ) -> Self {
+                return $name { data };
+            }
+        }
+
+        impl core::convert::AsRef<[$T; $N]> for $name {
+            /// Borrow the items as a primitive array.
+            fn as_ref( &self ) -> &[$T; $N] {
+                return &self.data;
+            }
+        }
+
         impl $name {
             /// Trait for returning an array of the odd-indexed numbers in self.
             #[allow(dead_code)]
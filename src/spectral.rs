@@ -0,0 +1,569 @@
+//! Spectral analysis of streaming signals.
+
+use fixed::traits::FixedSigned;
+use crate::utility;
+
+/// Calculate the power ratio `10log10(numerator/denominator)` in dB, of two wide integers.
+///
+/// The ratio is found as a difference of base 2 logarithms, so it does not need to fit the fixed type.
+/// Non-positive ratios return the minimum value of the type.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::spectral;
+///
+/// let y:FixedI32<U20> = spectral::ratio_db( 1, 1_000_000_000_000 );
+/// assert!{ -120.001 < y && y < -119.999 };
+/// ```
+pub fn ratio_db<T>( numerator:i128, denominator:i128 ) -> T
+    where T: FixedSigned
+{
+    if numerator <= 0 || denominator <= 0
+    {
+        return T::MIN;
+    }
+    let ten = T::from_num(10);
+    return ( wide_log2::<T>(numerator)-wide_log2::<T>(denominator) )*T::from_num(fixed::consts::LOG10_2)*ten;
+}
+
+/// log2 of a positive wide integer, as the position of its most significant bit and the log2 of its mantissa.
+fn wide_log2<T>( value:i128 ) -> T
+    where T: FixedSigned
+{
+    let exponent = 127-value.leading_zeros();
+    // The mantissa in the 1=<m<2 range, with 64 fractional bits.
+    let mantissa = if 64 <= exponent { value >> (exponent-64) } else { value << (64-exponent) };
+    return T::from_num(exponent)+utility::log2_fixed( T::from_num( fixed::types::I64F64::from_bits(mantissa) ) );
+}
+
+/// This macro implements a short-time Fourier transform (STFT) of a streaming signal.
+///
+/// Samples are pushed one at a time or in blocks of any length, and kept in an N-sample history.
+/// Every HOP samples, the history is windowed and transformed into a spectrum frame of N bins.
+/// The history starts out as zeros, so the first frame is ready after HOP samples.
+///
+/// ## Arguments
+/// * `name`        - The name of the STFT type. E.g. Stft.
+/// * `complex_arr` - The name of the complex array type of the frames, of length N. E.g. CArr16.
+/// * `fft`         - The name of an FFT type of `complex_arr`, see `declare_fft`. E.g. Fft16.
+/// * `N`           - The frame length.
+/// * `HOP`         - The number of samples between frames, in the range 1..=N.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate an STFT with a periodic analysis window, see `window::fill_periodic`.
+///
+/// # `::push`, `::push_block` and `::push_real_block`
+/// Push a complex sample, a block of complex samples, or a block of real samples, e.g. the `data` of an array.
+/// `push` returns a frame when one is ready. The block methods write the ready frames to `frames`, and return their count.
+/// `frame_count` gives the number of frames a block will produce. Frames beyond the length of `frames` are dropped.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::window::Window;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr16, Arr16, 16, FixedI32<U20> );
+/// ia::declare_fft!( Fft16, CArr16, 16, FixedI32<U20> );
+/// ia::declare_stft!( Stft, CArr16, Fft16, 16, 8, FixedI32<U20> );
+///
+/// let mut stft = Stft::new( Window::Hann );
+/// let mut frames = [CArr16::new_from_i32( 0, 0 ); 2];
+///
+/// // A tone at bin 4.
+/// let x = Arr16::new_from_f32_array( [1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0, 1.0, 0.0, -1.0, 0.0] );
+/// assert_eq!{ stft.frame_count( 16 ), 2 };
+/// assert_eq!{ stft.push_real_block( &x.data, &mut frames ), 2 };
+/// let magnitude = frames[1].mag();
+/// assert!{ 3.999 < magnitude[4] && magnitude[4] < 4.001 && magnitude[0] < 0.001 && magnitude[8] < 0.001 };
+/// ```
+///
+/// # `::reset`
+/// Clear the history.
+#[macro_export]
+macro_rules! declare_stft{
+    ( $name:ident, $complex_arr:ident, $fft:ident, $N:expr, $HOP:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Short-time Fourier transform.
+        pub struct $name{
+            window: [$T; $N],
+            history: [num::complex::Complex<$T>; $N],
+            position: usize,
+            count: usize,
+            fft: $fft,
+        }
+
+        impl $name {
+            /// Generate an STFT with a cleared history.
+            #[allow(dead_code)]
+            fn new( window:integer_array::window::Window ) -> Self {
+                let mut stft = $name {
+                    window: [<$T>::from_num(0); $N],
+                    history: [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $N],
+                    position: 0,
+                    count: 0,
+                    fft: $fft::new(),
+                };
+                integer_array::window::fill_periodic( window, &mut stft.window );
+                return stft;
+            }
+            /// Clear the history.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.history = [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $N];
+                self.position = 0;
+                self.count = 0;
+            }
+            /// The number of frames produced by pushing `length` more samples.
+            #[allow(dead_code)]
+            fn frame_count( &self, length:usize ) -> usize {
+                return (self.count+length)/$HOP;
+            }
+            /// Push a sample, and return a frame if one is ready.
+            #[allow(dead_code)]
+            fn push( &mut self, sample:num::complex::Complex<$T> ) -> Option<$complex_arr> {
+                self.history[self.position] = sample;
+                self.position = (self.position+1)%$N;
+                self.count += 1;
+                if self.count < $HOP
+                {
+                    return None;
+                }
+                self.count = 0;
+                // The oldest sample is at the write position.
+                let mut frame = $complex_arr::new_from_i32( 0, 0 );
+                for n in 0..$N {
+                    let item = self.history[(self.position+n)%$N];
+                    frame.data[n] = num::complex::Complex::new( item.re*self.window[n], item.im*self.window[n] );
+                }
+                return Some( self.fft.fft( &frame ) );
+            }
+            /// Push a block of complex samples, and return the number of frames written to `frames`.
+            #[allow(dead_code)]
+            fn push_block( &mut self, input:&[num::complex::Complex<$T>], frames:&mut [$complex_arr] ) -> usize {
+                let mut count = 0;
+                for item in input.iter() {
+                    if let Some(frame) = self.push( *item )
+                    {
+                        if count < frames.len()
+                        {
+                            frames[count] = frame;
+                            count += 1;
+                        }
+                    }
+                }
+                return count;
+            }
+            /// Push a block of real samples, and return the number of frames written to `frames`.
+            #[allow(dead_code)]
+            fn push_real_block( &mut self, input:&[$T], frames:&mut [$complex_arr] ) -> usize {
+                let mut count = 0;
+                for item in input.iter() {
+                    if let Some(frame) = self.push( num::complex::Complex::new( *item, <$T>::from_num(0) ) )
+                    {
+                        if count < frames.len()
+                        {
+                            frames[count] = frame;
+                            count += 1;
+                        }
+                    }
+                }
+                return count;
+            }
+        }
+    }
+}
+
+/// This macro implements the inverse short-time Fourier transform, by weighted overlap-add.
+///
+/// Each spectrum frame is inverse transformed, weighted by the synthesis window, and added into an N-sample overlap buffer.
+/// The oldest HOP samples are then complete, and are output normalized by the overlapped squared window, `Σw²(n+mHOP)`.
+/// With the same window and hop as the STFT, the output reconstructs the input, delayed by N-HOP samples.
+/// The reconstruction is exact for any window whose overlapped square has no zeros, e.g. Hann with HOP<=N/2.
+///
+/// ## Arguments
+/// * `name`        - The name of the ISTFT type. E.g. Istft.
+/// * `complex_arr` - The name of the complex array type of the frames, of length N. E.g. CArr16.
+/// * `fft`         - The name of an FFT type of `complex_arr`, see `declare_fft`. E.g. Fft16.
+/// * `N`           - The frame length.
+/// * `HOP`         - The number of samples between frames, in the range 1..=N.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate an ISTFT with a periodic synthesis window, see `window::fill_periodic`.
+///
+/// # `::push` and `::push_real`
+/// Push a spectrum frame, and write HOP complex or real output samples to `output`.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::window::Window;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_array_complex!( CArr8, Arr8, 8, FixedI32<U20> );
+/// ia::declare_fft!( Fft8, CArr8, 8, FixedI32<U20> );
+/// ia::declare_stft!( Stft, CArr8, Fft8, 8, 4, FixedI32<U20> );
+/// ia::declare_istft!( Istft, CArr8, Fft8, 8, 4, FixedI32<U20> );
+///
+/// let mut stft  = Stft::new( Window::Hann );
+/// let mut istft = Istft::new( Window::Hann );
+/// let x = Arr8::new_from_f32_array( [0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8] );
+///
+/// let mut frames = [CArr8::new_from_i32( 0, 0 ); 2];
+/// let mut y = Arr8::new_from_i32( 0 );
+/// for _ in 0..2 {
+///     stft.push_real_block( &x.data, &mut frames );
+///     istft.push_real( &frames[0], &mut y.data[..4] );
+///     istft.push_real( &frames[1], &mut y.data[4..] );
+/// }
+/// // The input, delayed by N-HOP = 4 samples.
+/// assert!{ (y[0]-x[4]).abs() < 0.001 && (y[3]-x[7]).abs() < 0.001 && (y[4]-x[0]).abs() < 0.001 };
+/// ```
+///
+/// # `::reset`
+/// Clear the overlap buffer.
+#[macro_export]
+macro_rules! declare_istft{
+    ( $name:ident, $complex_arr:ident, $fft:ident, $N:expr, $HOP:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Inverse short-time Fourier transform.
+        pub struct $name{
+            window: [$T; $N],
+            norm: [$T; $HOP],
+            overlap: [num::complex::Complex<$T>; $N],
+            fft: $fft,
+        }
+
+        impl $name {
+            /// Generate an ISTFT with a cleared overlap buffer.
+            #[allow(dead_code)]
+            fn new( window:integer_array::window::Window ) -> Self {
+                let mut istft = $name {
+                    window: [<$T>::from_num(0); $N],
+                    norm: [<$T>::from_num(0); $HOP],
+                    overlap: [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $N],
+                    fft: $fft::new(),
+                };
+                integer_array::window::fill_periodic( window, &mut istft.window );
+                for n in 0..$N {
+                    istft.norm[n%$HOP] += istft.window[n]*istft.window[n];
+                }
+                return istft;
+            }
+            /// Clear the overlap buffer.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.overlap = [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $N];
+            }
+            /// Push a spectrum frame, and write HOP output samples.
+            #[allow(dead_code)]
+            fn push( &mut self, frame:&$complex_arr, output:&mut [num::complex::Complex<$T>] ) {
+                let samples = self.fft.ifft( frame );
+                for n in 0..$N {
+                    self.overlap[n].re += samples[n].re*self.window[n];
+                    self.overlap[n].im += samples[n].im*self.window[n];
+                }
+                for n in 0..$HOP {
+                    output[n] = if self.norm[n] == <$T>::from_num(0)
+                    {
+                        num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) )
+                    }
+                    else
+                    {
+                        num::complex::Complex::new( self.overlap[n].re/self.norm[n], self.overlap[n].im/self.norm[n] )
+                    };
+                }
+                self.overlap.copy_within( $HOP.., 0 );
+                for item in self.overlap[$N-$HOP..].iter_mut() {
+                    *item = num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) );
+                }
+            }
+            /// Push a spectrum frame, and write the real part of HOP output samples.
+            #[allow(dead_code)]
+            fn push_real( &mut self, frame:&$complex_arr, output:&mut [$T] ) {
+                let mut samples = [num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) ); $HOP];
+                self.push( frame, &mut samples );
+                for n in 0..$HOP {
+                    output[n] = samples[n].re;
+                }
+            }
+        }
+    }
+}
+
+/// This macro implements Welch's power spectral density (PSD) estimator.
+///
+/// The signal is segmented, windowed and transformed by an STFT, and `|X|²` is averaged over the segments.
+/// Segments are only averaged once the STFT history is filled by N samples, so the cleared history does not bias the estimate.
+/// The overlap of the segments is set by the hop of the STFT, e.g. 50% for a hop of N/2.
+/// The estimate is two-sided, normalized per Hz as `Σ|X|²/(K·fs·Σw²)` for K segments and the sample rate fs.
+///
+/// The squared magnitudes are accumulated in wide integers, so long averages neither overflow nor lose small bins.
+///
+/// ## Arguments
+/// * `name`        - The name of the estimator type. E.g. Welch.
+/// * `stft`        - The name of an STFT type of `complex_arr`, see `declare_stft`. E.g. Stft.
+/// * `complex_arr` - The name of the complex array type of the STFT frames, of length N. E.g. CArr16.
+/// * `real_arr`    - The name of the real counterpart of the complex array type. E.g. Arr16.
+/// * `N`           - The segment length.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate an estimator with a periodic window, see `window::fill_periodic`.
+///
+/// # `::push`, `::push_block` and `::push_real_block`
+/// Push a complex sample, a block of complex samples, or a block of real samples, e.g. the `data` of an array.
+///
+/// # `::psd` and `::psd_db`
+/// Get the PSD averaged over the segments so far, in units²/Hz or dB/Hz, given the sample rate.
+/// The PSD is zero, or the minimum value of the type in dB, before the first segment.
+/// The sample rate must fit the type, e.g. given in kHz for types with few integer bits, which gives the PSD per kHz.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::window::Window;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+///
+/// ia::declare_array_complex!( CArr16, Arr16, 16, FixedI32<U20> );
+/// ia::declare_fft!( Fft16, CArr16, 16, FixedI32<U20> );
+/// ia::declare_stft!( Stft, CArr16, Fft16, 16, 8, FixedI32<U20> );
+/// ia::declare_welch!( Welch, Stft, CArr16, Arr16, 16, FixedI32<U20> );
+///
+/// // A complex tone at bin 2, with an amplitude of 0.5.
+/// let mut welch = Welch::new( Window::Hann );
+/// for n in 0..64 {
+///     let phase = 2.0*3.1415927*2.0*(n as f32)/16.0;
+///     welch.push( Complex::new( FixedI32::<U20>::from_num(0.5*phase.cos()), FixedI32::<U20>::from_num(0.5*phase.sin()) ) );
+/// }
+/// assert_eq!{ welch.segments(), 7 };
+///
+/// // |0.5·Σw|²/(fs·Σw²) = 16/(1000·6) per Hz at bin 2.
+/// let sample_rate = FixedI32::<U20>::from_num(1000);
+/// let psd = welch.psd( sample_rate );
+/// assert!{ 0.00266 < psd[2] && psd[2] < 0.00267 && psd[5] < 0.00001 };
+/// let psd = welch.psd_db( sample_rate );
+/// assert!{ -25.75 < psd[2] && psd[2] < -25.73 };
+/// ```
+///
+/// # `::segments` and `::reset`
+/// Get the number of averaged segments, or clear the average and the STFT history.
+#[macro_export]
+macro_rules! declare_welch{
+    ( $name:ident, $stft:ident, $complex_arr:ident, $real_arr:ident, $N:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Welch power spectral density estimator.
+        pub struct $name{
+            stft: $stft,
+            window_power: i128,
+            sum: [i128; $N],
+            segments: usize,
+            filled: usize,
+        }
+
+        impl $name {
+            /// Generate an estimator with a cleared average.
+            #[allow(dead_code)]
+            fn new( window:integer_array::window::Window ) -> Self {
+                let mut coefficients = [<$T>::from_num(0); $N];
+                integer_array::window::fill_periodic( window, &mut coefficients );
+                // Σw², with the fractional bits of the type.
+                let mut window_power:i128 = 0;
+                for item in coefficients.iter() {
+                    window_power += (item.to_bits() as i128)*(item.to_bits() as i128);
+                }
+                $name {
+                    stft: $stft::new( window ),
+                    window_power: window_power >> <$T>::FRAC_NBITS,
+                    sum: [0; $N],
+                    segments: 0,
+                    filled: 0,
+                }
+            }
+            /// Clear the average and the STFT history.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                self.stft.reset();
+                self.sum = [0; $N];
+                self.segments = 0;
+                self.filled = 0;
+            }
+            /// The number of averaged segments.
+            #[allow(dead_code)]
+            fn segments( &self ) -> usize {
+                return self.segments;
+            }
+            /// Add the squared magnitude of a frame to the sum, with twice the fractional bits of the type.
+            #[allow(dead_code)]
+            fn accumulate( &mut self, frame:&$complex_arr ) {
+                for k in 0..$N {
+                    let re = frame[k].re.to_bits() as i128;
+                    let im = frame[k].im.to_bits() as i128;
+                    self.sum[k] += re*re+im*im;
+                }
+                self.segments += 1;
+            }
+            /// Push a complex sample.
+            #[allow(dead_code)]
+            fn push( &mut self, sample:num::complex::Complex<$T> ) {
+                if self.filled < $N
+                {
+                    self.filled += 1;
+                }
+                if let Some(frame) = self.stft.push( sample )
+                {
+                    if $N <= self.filled
+                    {
+                        self.accumulate( &frame );
+                    }
+                }
+            }
+            /// Push a block of complex samples.
+            #[allow(dead_code)]
+            fn push_block( &mut self, input:&[num::complex::Complex<$T>] ) {
+                for item in input.iter() {
+                    self.push( *item );
+                }
+            }
+            /// Push a block of real samples.
+            #[allow(dead_code)]
+            fn push_real_block( &mut self, input:&[$T] ) {
+                for item in input.iter() {
+                    self.push( num::complex::Complex::new( *item, <$T>::from_num(0) ) );
+                }
+            }
+            /// The denominator `K·fs·Σw²` of the PSD, in bits.
+            #[allow(dead_code)]
+            fn denominator( &self, sample_rate:$T ) -> i128 {
+                return (self.segments as i128)*(sample_rate.to_bits() as i128)*self.window_power;
+            }
+            /// The PSD in units²/Hz.
+            #[allow(dead_code)]
+            fn psd( &self, sample_rate:$T ) -> $real_arr {
+                let mut r_array = $real_arr::new_from_i32( 0 );
+                let denominator = self.denominator( sample_rate );
+                if denominator <= 0
+                {
+                    return r_array;
+                }
+                for k in 0..$N {
+                    // Σ|X|²/(K·fs·Σw²), where the fractional bits of fs and Σw² cancel those of the sum.
+                    let bits = (self.sum[k] << <$T>::FRAC_NBITS)/denominator;
+                    r_array[k] = integer_array::utility::from_wide_bits::<$T>( bits );
+                }
+                return r_array;
+            }
+            /// The PSD in dB/Hz.
+            #[allow(dead_code)]
+            fn psd_db( &self, sample_rate:$T ) -> $real_arr {
+                let mut r_array = $real_arr::new_from_i32( 0 );
+                let denominator = self.denominator( sample_rate );
+                for k in 0..$N {
+                    r_array[k] = integer_array::spectral::ratio_db( self.sum[k], denominator );
+                }
+                return r_array;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use crate::window::Window;
+    use fixed::{types::extra::U20, FixedI32};
+
+    #[test]
+    fn stft_reconstructs_the_input() {
+        integer_array::declare_array_complex!( CArr32, Arr32, 32, FixedI32<U20> );
+        integer_array::declare_fft!( Fft32, CArr32, 32, FixedI32<U20> );
+        integer_array::declare_stft!( Stft, CArr32, Fft32, 32, 8, FixedI32<U20> );
+        integer_array::declare_istft!( Istft, CArr32, Fft32, 32, 8, FixedI32<U20> );
+
+        for window in [Window::Hann, Window::Hamming, Window::Rectangular].iter() {
+            let mut stft  = Stft::new( *window );
+            let mut istft = Istft::new( *window );
+            let input = |n:usize| num::complex::Complex::new( FixedI32::<U20>::from_num( (0.1*n as f64).sin() ),
+                                                              FixedI32::<U20>::from_num( (0.37*n as f64).cos() ) );
+            // Blocks of a length unrelated to the hop.
+            let mut output = [num::complex::Complex::new( FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(0) ); 8];
+            let mut frames = [CArr32::new_from_i32( 0, 0 ); 2];
+            let mut n_out = 0;
+            for block in 0..20 {
+                let samples: [_; 13] = core::array::from_fn( |n| input(13*block+n) );
+                assert_eq!{ stft.frame_count( 13 ), (13*block%8+13)/8 };
+                let count = stft.push_block( &samples, &mut frames );
+                for frame in frames[..count].iter() {
+                    istft.push( frame, &mut output );
+                    for item in output.iter() {
+                        if 24 <= n_out
+                        {
+                            let expected = input(n_out-24);
+                            assert!{ (item.re-expected.re).abs() < 0.001 && (item.im-expected.im).abs() < 0.001 };
+                        }
+                        n_out += 1;
+                    }
+                }
+            }
+            assert_eq!{ n_out, 13*20/8*8 };
+        }
+    }
+
+    #[test]
+    fn stft_drops_frames_beyond_the_slice() {
+        integer_array::declare_array_complex!( CArr16, Arr16, 16, FixedI32<U20> );
+        integer_array::declare_fft!( Fft16, CArr16, 16, FixedI32<U20> );
+        integer_array::declare_stft!( Stft, CArr16, Fft16, 16, 4, FixedI32<U20> );
+
+        let mut stft = Stft::new( Window::Hann );
+        let mut frames = [CArr16::new_from_i32( 0, 0 ); 1];
+        let input = [FixedI32::<U20>::from_num(1); 16];
+        assert_eq!{ stft.frame_count( 16 ), 4 };
+        assert_eq!{ stft.push_real_block( &input, &mut frames ), 1 };
+        assert_eq!{ stft.frame_count( 0 ), 0 };
+    }
+
+    #[test]
+    fn welch_power_matches_the_signal_power() {
+        integer_array::declare_array_complex!( CArr32, Arr32, 32, FixedI32<U20> );
+        integer_array::declare_fft!( Fft32, CArr32, 32, FixedI32<U20> );
+        integer_array::declare_stft!( Stft, CArr32, Fft32, 32, 16, FixedI32<U20> );
+        integer_array::declare_welch!( Welch, Stft, CArr32, Arr32, 32, FixedI32<U20> );
+
+        // A complex tone of constant magnitude, so each windowed segment holds A²·Σw² in total.
+        let amplitude = 0.3f64;
+        let sample_rate = FixedI32::<U20>::from_num(100);
+        let mut welch = Welch::new( Window::Hann );
+        for n in 0..320 {
+            let phase = 0.77*n as f64;
+            welch.push( num::complex::Complex::new( FixedI32::<U20>::from_num(amplitude*phase.cos()), FixedI32::<U20>::from_num(amplitude*phase.sin()) ) );
+        }
+        assert_eq!{ welch.segments(), 19 };
+        let psd = welch.psd( sample_rate );
+        let power: f64 = psd.data.iter().map( |item| item.to_num::<f64>()*100.0/32.0 ).sum();
+        assert!{ (power-amplitude*amplitude).abs() < 0.001 };
+
+        let psd_db = welch.psd_db( sample_rate );
+        for k in 0..32 {
+            if 0.001 < psd[k]
+            {
+                let expected = 10.0*psd[k].to_num::<f64>().log10();
+                assert!{ (psd_db[k].to_num::<f64>()-expected).abs() < 0.01 };
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//! Lookup tables evaluated at compile time.
+//!
+//! The tables are calculated in double precision by `const` functions, and quantized to the fixed type.
+//! Declared as `const` or `static` items, they are placed in read-only memory (flash) rather than calculated at start-up.
+
+use core::f64::consts::{PI, FRAC_PI_2, TAU};
+
+/// Calculate sin(x) in double precision, usable in `const` context.
+///
+/// The angle is reduced to the -π/2=<x<=π/2 range, and the Taylor series is evaluated to the 23rd power.
+///
+/// # Example
+///
+/// ```
+/// use integer_array::table;
+/// const SIN: f64 = table::sin( 3.0 );
+/// assert!{ (SIN-0.1411200080598672).abs() < 1e-15 };
+/// ```
+pub const fn sin( x:f64 ) -> f64 {
+    let mut x = x%TAU;
+    if PI < x
+    {
+        x -= TAU;
+    }
+    else if x < -PI
+    {
+        x += TAU;
+    }
+    if FRAC_PI_2 < x
+    {
+        x = PI-x;
+    }
+    else if x < -FRAC_PI_2
+    {
+        x = -PI-x;
+    }
+
+    let mut term = x;
+    let mut sum  = x;
+    let mut n = 1;
+    while n < 12
+    {
+        term *= -x*x/((2*n*(2*n+1)) as f64);
+        sum  += term;
+        n += 1;
+    }
+    return sum;
+}
+
+/// Calculate cos(x) in double precision, usable in `const` context.
+pub const fn cos( x:f64 ) -> f64 {
+    return sin( x+FRAC_PI_2 );
+}
+
+/// Quantize a value to the bits of a fixed type with `frac_nbits` fractional bits, rounding to nearest.
+///
+/// The result is saturated to the `min..=max` range of the bits, e.g. `FixedI32::<U31>::MAX.to_bits()` for values of one.
+pub const fn fixed_bits( value:f64, frac_nbits:u32, min:i128, max:i128 ) -> i128 {
+    let scaled = value*((1u128 << frac_nbits) as f64);
+    let bits = if 0.0 <= scaled { (scaled+0.5) as i128 } else { (scaled-0.5) as i128 };
+    if bits < min
+    {
+        return min;
+    }
+    if max < bits
+    {
+        return max;
+    }
+    return bits;
+}
+
+/// Generate a quarter-wave sine table `sin(πk/2N)` for `k` in `0..=N`, as a constant expression.
+///
+/// The table covers the first quarter of a period of both sine and cosine, see `declare_trig_table`.
+///
+/// ## Arguments
+/// * `N` - The number of intervals in the quarter wave. The table has N+1 items.
+/// * `T` - The fixed type of the items.
+///
+/// # Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// static QUARTER_SINE: [FixedI32<U20>; 5] = ia::quarter_sine_table!( 4, FixedI32<U20> );
+/// assert_eq!{ QUARTER_SINE[0], 0 };
+/// assert_eq!{ QUARTER_SINE[2].to_num::<f32>(), 0.70710659 };
+/// assert_eq!{ QUARTER_SINE[4], 1 };
+/// ```
+#[macro_export]
+macro_rules! quarter_sine_table{
+    ( $N:expr, $T:ty ) => {
+        {
+            let mut table = [<$T>::ZERO; $N+1];
+            let mut k = 0;
+            while k <= $N
+            {
+                let value = integer_array::table::sin( core::f64::consts::FRAC_PI_2*(k as f64)/(($N) as f64) );
+                table[k] = <$T>::from_bits( integer_array::table::fixed_bits( value, <$T>::FRAC_NBITS, <$T>::MIN.to_bits() as i128, <$T>::MAX.to_bits() as i128 ) as _ );
+                k += 1;
+            }
+            table
+        }
+    }
+}
+
+/// Generate the FFT twiddle factors `W_N^k = e^(-j2πk/N)` for `k` in `0..N`, as a constant expression.
+///
+/// The table has the layout of `fft::full_twiddles`, and serves `fft::mixed_radix`.
+///
+/// ## Arguments
+/// * `N` - The length of the FFT.
+/// * `T` - The fixed type of the items.
+///
+/// # Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+///
+/// static TWIDDLES: [Complex<FixedI32<U20>>; 4] = ia::twiddle_table!( 4, FixedI32<U20> );
+/// assert_eq!{ TWIDDLES[1].re, 0 };
+/// assert_eq!{ TWIDDLES[1].im, -1 };
+/// ```
+#[macro_export]
+macro_rules! twiddle_table{
+    ( $N:expr, $T:ty ) => {
+        {
+            let mut table = [num::complex::Complex::new( <$T>::ZERO, <$T>::ZERO ); $N];
+            let mut k = 0;
+            while k < $N
+            {
+                let phase = -core::f64::consts::TAU*(k as f64)/(($N) as f64);
+                let re = integer_array::table::fixed_bits( integer_array::table::cos( phase ), <$T>::FRAC_NBITS, <$T>::MIN.to_bits() as i128, <$T>::MAX.to_bits() as i128 );
+                let im = integer_array::table::fixed_bits( integer_array::table::sin( phase ), <$T>::FRAC_NBITS, <$T>::MIN.to_bits() as i128, <$T>::MAX.to_bits() as i128 );
+                table[k] = num::complex::Complex::new( <$T>::from_bits( re as _ ), <$T>::from_bits( im as _ ) );
+                k += 1;
+            }
+            table
+        }
+    }
+}
+
+/// This macro implements sine and cosine through a linearly interpolated quarter-wave lookup table.
+///
+/// The table is generated at compile time by `quarter_sine_table`, and kept as an associated constant.
+/// The angle is wrapped to the -π=<x<π range, and folded into the first quarter by the symmetries of the sine.
+/// The interpolation error is about `(π/2N)^2/8`, e.g. 5e-6 for N=256.
+///
+/// The type implements `trait_definitions::TrigTable`, which is used by `sin_table` and `cos_table` on real arrays.
+///
+/// ## Arguments
+/// * `name` - The name of the table type. E.g. Trig256.
+/// * `N`    - The number of intervals in the quarter wave.
+/// * `T`    - The fixed type of the elements. Must be able to hold π.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use fixed::{types::extra::U20, FixedI32};
+///
+/// ia::declare_trig_table!( Trig256, 256, FixedI32<U20> );
+///
+/// let y = Trig256::sin( FixedI32::<U20>::from_num(7.0) );
+/// assert!{ 0.65698 < y && y < 0.65699 };
+/// let y = Trig256::cos( FixedI32::<U20>::from_num(-2.0) );
+/// assert!{ -0.41615 < y && y < -0.41614 };
+/// ```
+#[macro_export]
+macro_rules! declare_trig_table{
+    ( $name:ident, $N:expr, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Quarter-wave sine lookup table.
+        pub struct $name;
+
+        impl $name {
+            /// The quarter-wave sine table `sin(πk/2N)` for `k` in `0..=N`.
+            #[allow(dead_code)]
+            const QUARTER_SINE: [$T; $N+1] = integer_array::quarter_sine_table!( $N, $T );
+        }
+
+        impl integer_array::trait_definitions::TrigTable<$T> for $name {
+            /// Sine through the interpolated table.
+            fn sin( x:$T ) -> $T {
+                let x = fixed_trigonometry::wrap_phase( x );
+                // The position in the half period, in table intervals, with FRAC_NBITS fractional bits.
+                let quarters = x.abs()*<$T>::from_num( fixed::consts::FRAC_2_PI );
+                let mut position = quarters.to_bits() as i128*($N as i128);
+                let quarter = ($N as i128) << <$T>::FRAC_NBITS;
+                if quarter < position
+                {
+                    position = 2*quarter-position;
+                }
+                let index = ( position >> <$T>::FRAC_NBITS ) as usize;
+                let value = if $N <= index
+                {
+                    Self::QUARTER_SINE[$N]
+                }
+                else
+                {
+                    let fraction = <$T>::from_bits( ( position & ((1i128 << <$T>::FRAC_NBITS)-1) ) as _ );
+                    Self::QUARTER_SINE[index]+(Self::QUARTER_SINE[index+1]-Self::QUARTER_SINE[index])*fraction
+                };
+                if x < <$T>::ZERO
+                {
+                    return -value;
+                }
+                return value;
+            }
+            /// Cosine through the interpolated table, as a phase shifted sine.
+            fn cos( x:$T ) -> $T {
+                return Self::sin( fixed_trigonometry::wrap_phase( x )+<$T>::from_num( fixed::consts::FRAC_PI_2 ) );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use crate::trait_definitions::TrigTable;
+    use fixed::{types::extra::U20, FixedI32};
+
+    #[test]
+    fn table_sin_and_cos_are_accurate() {
+        integer_array::declare_trig_table!( Trig256, 256, FixedI32<U20> );
+        for n in -700..700 {
+            let x = n as f64/100.0;
+            let y = Trig256::sin( FixedI32::<U20>::from_num(x) ).to_num::<f64>();
+            assert!{ (y-x.sin()).abs() < 1e-5 };
+            let y = Trig256::cos( FixedI32::<U20>::from_num(x) ).to_num::<f64>();
+            assert!{ (y-x.cos()).abs() < 1e-5 };
+        }
+    }
+
+    #[test]
+    fn const_sin_is_accurate() {
+        for n in -100..100 {
+            let x = n as f64/10.0;
+            assert!{ (super::sin(x)-x.sin()).abs() < 1e-14 };
+        }
+    }
+}
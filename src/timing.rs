@@ -0,0 +1,316 @@
+use fixed::traits::FixedSigned;
+use num::complex::Complex;
+
+/// The timing-error detector of a symbol synchronizer.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TimingDetector {
+    /// The Gardner detector, `Re{y*[k-½]·(y[k]-y[k-1])}`.
+    /// Uses the sample midway between symbols, and is independent of the carrier phase and of decisions.
+    Gardner,
+    /// The decision-directed Mueller-Müller detector, `Re{d*[k]·y[k-1]-d*[k-1]·y[k]}`, with the decisions `d` as the signs of the parts.
+    /// Needs one sample per symbol only, but assumes the carrier is recovered, and BPSK or QPSK symbols of unit amplitude.
+    MuellerMuller,
+}
+
+/// The Gardner timing error of a symbol, the midpoint before it, and the previous symbol.
+///
+/// For band-limited pulses, the error is positive when the symbols are sampled late.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::timing;
+///
+/// let value = |re:f32| Complex::new( FixedI32::<U20>::from_num(re), FixedI32::<U20>::from_num(0) );
+/// // A transition from -1 to 1, sampled late, has passed zero at the midpoint.
+/// assert_eq!{ timing::gardner_error( value(-0.875), value(0.25), value(1.0) ), 0.46875 };
+/// ```
+pub fn gardner_error<T>( previous:Complex<T>, middle:Complex<T>, current:Complex<T> ) -> T
+    where T: FixedSigned
+{
+    return middle.re*(current.re-previous.re)+middle.im*(current.im-previous.im);
+}
+
+/// The Mueller-Müller timing error of a symbol and the previous symbol, with decisions as the signs of their parts.
+///
+/// For band-limited pulses, the error is positive when the symbols are sampled late.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+/// use integer_array::timing;
+///
+/// let value = |re:f32| Complex::new( FixedI32::<U20>::from_num(re), FixedI32::<U20>::from_num(0) );
+/// // Sampled late, the previous symbol has picked up part of the current one, and the current one lost some of itself.
+/// assert_eq!{ timing::mueller_muller_error( value(-0.75), value(0.875) ), 0.125 };
+/// ```
+pub fn mueller_muller_error<T>( previous:Complex<T>, current:Complex<T> ) -> T
+    where T: FixedSigned
+{
+    let (decision_previous, decision_current) = (previous.re.signum(), current.re.signum());
+    let (decision_previous_im, decision_current_im) = (previous.im.signum(), current.im.signum());
+    return decision_current*previous.re+decision_current_im*previous.im-decision_previous*current.re-decision_previous_im*current.im;
+}
+
+/// This macro implements symbol timing recovery for oversampled complex arrays.
+///
+/// A timing-error detector, see `timing::TimingDetector`, drives a proportional-integral loop filter,
+/// which steers when the input is sampled by a cubic interpolator, see `interpolation::cubic_hermite`.
+/// The synchronizer outputs one sample per symbol, so the number of symbols per block varies with the timing.
+/// The oversampling may be fractional, but must be at least two for the Gardner detector.
+///
+/// The interpolator delays the input by one sample. The state of the loop and the interpolator is kept across blocks.
+///
+/// ## Arguments
+/// * `name`        - The name of the synchronizer type. E.g. SymbolSync.
+/// * `complex_arr` - The name of the complex array type to process. E.g. CArr32.
+/// * `T`           - The fixed type of the elements.
+///
+/// # `::new`
+/// Generate a synchronizer.
+///
+/// ## Arguments
+/// * `samples_per_symbol` - The nominal number of input samples per symbol. At least one, and at least two for the Gardner detector, or the function panics.
+/// * `detector`           - The timing-error detector.
+/// * `bandwidth`          - The noise bandwidth of the loop, relative to the symbol rate. E.g. 0.01.
+/// * `damping`            - The damping factor of the loop. E.g. 0.707.
+///
+/// # `::push`
+/// Push a sample, and return a symbol if one is due.
+///
+/// # `::process`
+/// Push a block of samples, write the symbols into a slice, and return their number. Symbols beyond the length of the slice are dropped.
+///
+/// # `::error` and `::period`
+/// Get the latest timing error, and the current estimate of the symbol period in samples.
+///
+/// # `::reset`
+/// Clear the state of the loop and the interpolator.
+///
+/// ## Example
+///
+/// ```rust
+/// use integer_array as ia;
+/// use ia::trait_definitions::*;
+/// use ia::timing::TimingDetector;
+/// use fixed::{types::extra::U20, FixedI32};
+/// use num::complex::Complex;
+///
+/// ia::declare_array_complex!( CArr32, Arr32, 32, FixedI32<U20> );
+/// ia::declare_symbol_sync!( SymbolSync, CArr32, FixedI32<U20> );
+///
+/// // Alternating BPSK symbols at four samples per symbol, with raised-cosine pulses sampled a quarter of a symbol late.
+/// let mut sync = SymbolSync::new( FixedI32::<U20>::from_num(4), TimingDetector::Gardner, FixedI32::<U20>::from_num(0.05), FixedI32::<U20>::from_num(0.707) );
+/// let mut symbols = [Complex::new( FixedI32::<U20>::from_num(0), FixedI32::<U20>::from_num(0) ); 16];
+/// let mut count = 0;
+/// for block in 0..16 {
+///     let mut x = CArr32::new_from_i32( 0, 0 );
+///     for (n, item) in x.data.iter_mut().enumerate() {
+///         let t = (32*block+n) as f32/4.0-0.25;
+///         item.re = FixedI32::<U20>::from_num( (core::f32::consts::PI*t).cos() );
+///     }
+///     count = sync.process( &x, &mut symbols );
+/// }
+/// assert_eq!{ count, 8 };
+/// for symbol in symbols[..count].iter() {
+///     assert!{ 0.99 < symbol.re.abs() };
+/// }
+/// ```
+#[macro_export]
+macro_rules! declare_symbol_sync{
+    ( $name:ident, $complex_arr:ident, $T:ty ) => {
+
+        #[derive(Copy, Clone, Debug, PartialEq)]
+        /// Symbol timing recovery.
+        pub struct $name{
+            half_period: $T,
+            detector: integer_array::timing::TimingDetector,
+            proportional: $T,
+            integral: $T,
+            // The four most recent samples, the oldest first.
+            history: [num::complex::Complex<$T>; 4],
+            // The time until the next strobe, in samples from the second oldest sample in the history.
+            countdown: $T,
+            integrator: $T,
+            symbol_is_next: bool,
+            previous: num::complex::Complex<$T>,
+            middle: num::complex::Complex<$T>,
+            error: $T,
+        }
+
+        impl $name {
+            /// Generate a synchronizer.
+            #[allow(dead_code)]
+            fn new( samples_per_symbol:$T, detector:integer_array::timing::TimingDetector, bandwidth:$T, damping:$T ) -> Self {
+                assert!{ <$T>::from_num(1) <= samples_per_symbol };
+                if detector == integer_array::timing::TimingDetector::Gardner
+                {
+                    assert!{ <$T>::from_num(2) <= samples_per_symbol };
+                }
+                let (proportional, integral) = integer_array::pll::loop_gains( bandwidth, damping );
+                let zero = num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) );
+                $name {
+                    half_period: samples_per_symbol/2,
+                    detector,
+                    proportional,
+                    integral,
+                    history: [zero; 4],
+                    countdown: <$T>::from_num(0),
+                    integrator: <$T>::from_num(0),
+                    symbol_is_next: true,
+                    previous: zero,
+                    middle: zero,
+                    error: <$T>::from_num(0),
+                }
+            }
+            /// Clear the state of the loop and the interpolator.
+            #[allow(dead_code)]
+            fn reset( &mut self ) {
+                let zero = num::complex::Complex::new( <$T>::from_num(0), <$T>::from_num(0) );
+                self.history = [zero; 4];
+                self.countdown = <$T>::from_num(0);
+                self.integrator = <$T>::from_num(0);
+                self.symbol_is_next = true;
+                self.previous = zero;
+                self.middle = zero;
+                self.error = <$T>::from_num(0);
+            }
+            /// The latest timing error.
+            #[allow(dead_code)]
+            fn error( &self ) -> $T {
+                return self.error;
+            }
+            /// The current estimate of the symbol period, in samples.
+            #[allow(dead_code)]
+            fn period( &self ) -> $T {
+                return 2*self.half_period-self.integrator;
+            }
+            /// Push a sample, and return a symbol if one is due.
+            #[allow(dead_code)]
+            fn push( &mut self, item:num::complex::Complex<$T> ) -> Option<num::complex::Complex<$T>> {
+                self.history.copy_within( 1..4, 0 );
+                self.history[3] = item;
+
+                let mut r_symbol = None;
+                // The strobe lies between the two middle samples of the history, where the cubic interpolator is centered.
+                while self.countdown < <$T>::from_num(1) {
+                    let strobe = integer_array::interpolation::cubic_hermite( &self.history, self.countdown+<$T>::from_num(1) );
+                    if !self.symbol_is_next
+                    {
+                        self.middle = strobe;
+                        self.countdown += self.half_period;
+                    }
+                    else
+                    {
+                        self.error = match self.detector {
+                            integer_array::timing::TimingDetector::Gardner => integer_array::timing::gardner_error( self.previous, self.middle, strobe ),
+                            integer_array::timing::TimingDetector::MuellerMuller => integer_array::timing::mueller_muller_error( self.previous, strobe ),
+                        };
+                        self.integrator += self.integral*self.error;
+                        // A late strobe moves the next one earlier. The step is limited to half a symbol.
+                        let adjustment = (self.proportional*self.error+self.integrator).clamp( -self.half_period/2, self.half_period/2 );
+                        self.countdown += self.half_period-adjustment;
+                        self.previous = strobe;
+                        r_symbol = Some( strobe );
+                    }
+                    self.symbol_is_next = !self.symbol_is_next;
+                }
+                self.countdown -= <$T>::from_num(1);
+                return r_symbol;
+            }
+            /// Push a block of samples, write the symbols into a slice, and return their number.
+            #[allow(dead_code)]
+            fn process( &mut self, input:&$complex_arr, output:&mut [num::complex::Complex<$T>] ) -> usize {
+                let mut count = 0;
+                for item in input.data.iter() {
+                    if let Some(symbol) = self.push( *item )
+                    {
+                        if count < output.len()
+                        {
+                            output[count] = symbol;
+                            count += 1;
+                        }
+                    }
+                }
+                return count;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as integer_array;
+    use super::TimingDetector;
+    use fixed::{types::extra::U20, FixedI32};
+    use num::complex::Complex;
+
+    type T = FixedI32<U20>;
+
+    /// A raised-cosine pulse with a roll-off of one half, at a time in symbols.
+    fn pulse( t:f64 ) -> f64 {
+        let pi = core::f64::consts::PI;
+        if t == 0.0
+        {
+            return 1.0;
+        }
+        if (2.0*t).abs() == 2.0
+        {
+            return pi/4.0*(pi*t).sin()/(pi*t);
+        }
+        return (pi*t).sin()/(pi*t)*(pi*0.5*t).cos()/(1.0-t*t);
+    }
+
+    #[test]
+    fn both_detectors_lock_on_qpsk() {
+        integer_array::declare_array_complex!( CArr32, Arr32, 32, T );
+        integer_array::declare_symbol_sync!( SymbolSync, CArr32, T );
+
+        // Pseudo-random QPSK symbols, and a clock offset of 0.1% with a starting phase of 0.4 symbols.
+        let mut seed = 5u32;
+        let symbols:[(f64, f64); 512] = core::array::from_fn( |_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ( if seed >> 16 & 1 == 0 { 0.7 } else { -0.7 }, if seed >> 17 & 1 == 0 { 0.7 } else { -0.7 } )
+        } );
+        let signal = |n:usize| {
+            let t = n as f64/3.0/1.001+0.4;
+            let (mut re, mut im) = (0.0, 0.0);
+            for (m, symbol) in symbols.iter().enumerate().skip( (t as usize).saturating_sub(8) ).take( 16 ) {
+                let p = pulse( t-m as f64 );
+                re += symbol.0*p;
+                im += symbol.1*p;
+            }
+            return Complex::new( T::from_num(re), T::from_num(im) );
+        };
+
+        for detector in [TimingDetector::Gardner, TimingDetector::MuellerMuller] {
+            let mut sync = SymbolSync::new( T::from_num(3), detector, T::from_num(0.02), T::from_num(0.707) );
+            let mut output = [Complex::new( T::from_num(0), T::from_num(0) ); 16];
+            for block in 0..45 {
+                let input = CArr32 { data: core::array::from_fn( |n| signal( 32*block+n ) ) };
+                let count = sync.process( &input, &mut output );
+                if 30 <= block
+                {
+                    for symbol in output[..count].iter() {
+                        assert!{ 0.6 < symbol.re.abs() && symbol.re.abs() < 0.8 };
+                        assert!{ 0.6 < symbol.im.abs() && symbol.im.abs() < 0.8 };
+                    }
+                }
+            }
+            // The loop has found the longer period.
+            assert!{ (sync.period().to_num::<f64>()-3.003).abs() < 0.002 };
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn gardner_needs_two_samples_per_symbol() {
+        integer_array::declare_array_complex!( CArr32, Arr32, 32, T );
+        integer_array::declare_symbol_sync!( SymbolSync, CArr32, T );
+        SymbolSync::new( T::from_num(1.5), TimingDetector::Gardner, T::from_num(0.02), T::from_num(0.707) );
+    }
+}
@@ -15,6 +15,7 @@ pub trait Initializers {
     fn zeros()      -> Self;
 }
 
+#[allow(clippy::len_without_is_empty)]
 pub trait Len {
     fn len( &self ) -> usize;
 }
@@ -39,6 +40,11 @@ pub trait Atan {
     fn atan( &self )  -> Self;
 }
 
+pub trait TrigTable<T> {
+    fn sin( x:T ) -> T;
+    fn cos( x:T ) -> T;
+}
+
 pub trait WrapPhase {
     fn wrap_phase( &self )  -> Self;
 }
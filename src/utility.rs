@@ -1,11 +1,54 @@
-use fixed::traits::Fixed;
+use core::convert::{TryFrom, TryInto};
+use fixed::traits::{Fixed, FixedSigned};
+use crate::polynomial::Polynomial;
+
+/// The bits of a fixed-point number, widened to i128.
+///
+/// Used for intermediate results which would overflow the type, e.g. sums of products.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::utility as util;
+/// assert_eq!{ util::wide_bits( FixedI32::<U20>::from_num(-1.5) ), -3 << 19 };
+/// ```
+pub fn wide_bits<T>( value:T ) -> i128
+    where T: Fixed
+{
+    return value.to_bits().try_into().unwrap_or(0);
+}
+
+/// A fixed-point number from wide bits, with saturation at the range of the type.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::utility as util;
+/// assert_eq!{ util::from_wide_bits::<FixedI32<U20>>( -3 << 19 ), -1.5 };
+/// assert_eq!{ util::from_wide_bits::<FixedI32<U20>>( 1 << 40 ), FixedI32::<U20>::MAX };
+/// ```
+pub fn from_wide_bits<T>( bits:i128 ) -> T
+    where T: Fixed
+{
+    if wide_bits(T::MAX) < bits
+    {
+        return T::MAX;
+    }
+    if bits < wide_bits(T::MIN)
+    {
+        return T::MIN;
+    }
+    return T::from_bits( <T as Fixed>::Bits::try_from( bits ).unwrap_or( T::ZERO.to_bits() ) );
+}
 
 /// Rase integer to an integer-valued power.
 /// base^power.
 pub fn powi( base:i32, power:usize ) -> i32 {
     let mut temp:i32 = base;
     for _i in 0..power-1 {
-        temp = temp*base;
+        temp *= base;
     }
     return temp;
 }
@@ -29,7 +72,7 @@ pub fn fixed_powi<T>( base:T, power:usize ) -> T
 {
     let mut temp:T = base;
     for _i in 0..power-1 {
-        temp = temp*base;
+        temp *= base;
     }
     return temp;
 }
@@ -47,15 +90,22 @@ pub fn fixed_powi<T>( base:T, power:usize ) -> T
 /// use integer_array::utility as util;
 /// let x = util::sqrt( FixedI32::<U20>::from_num(110), FixedI32::<U20>::from_num(0.025) );
 /// assert_eq!{  x.to_num::<f32>(), 10.488159f32 };
+///
+/// let x = util::sqrt( FixedI32::<U20>::from_num(0.25), FixedI32::<U20>::from_num(0.001) );
+/// assert_eq!{  x.to_num::<f32>(), 0.50001144f32 };
 /// ``` 
 pub fn sqrt<T>( item:T, error:T ) -> T
     where T: fixed::traits::Fixed
 {
+    if item <= T::from_num(0)
+    {
+        return T::from_num(0);
+    }
     // Initial approximation
     let mut root: T = item/T::from_num(2);
     let mut y:T = T::from_num(1);
     // Accuracy level
-    while  error <= root - y
+    while  error <= if root < y { y - root } else { root - y }
     {
         root = (root + y) / T::from_num(2);
         y = item / root;
@@ -63,6 +113,51 @@ pub fn sqrt<T>( item:T, error:T ) -> T
     return root;
 }
 
+/// Calculate sin(x) using the first-quarter Taylor approximation in `fixed_trigonometry`.
+/// The angle is wrapped to the -π=<x<π range before the calculation.
+/// 
+/// # Example
+/// 
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::utility as util;
+/// let y = util::sin_precise_fixed( FixedI32::<U20>::from_num(7.0) );
+/// assert_eq!{ y.to_num::<f32>(), 0.6569872 };
+/// ``` 
+pub fn sin_precise_fixed<T>( x:T ) -> T
+    where T: FixedSigned
+{
+    let sinx = fixed_trigonometry::sin( fixed_trigonometry::wrap_phase(x) );
+    // The polynomial slightly overshoots ±1 around ±π/2.
+    if T::from_num(1) < sinx
+    {
+        return T::from_num(1);
+    }
+    if sinx < T::from_num(-1)
+    {
+        return T::from_num(-1);
+    }
+    return sinx;
+}
+
+/// Calculate cos(x) as a phase shifted sine.
+/// The polynomial cosine in `fixed_trigonometry` has an error of about 0.02 close to ±π/2, while the sine does not.
+/// The angle is wrapped to the -π=<x<π range before the calculation.
+/// 
+/// # Example
+/// 
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::utility as util;
+/// let y = util::cos_precise_fixed( FixedI32::<U20>::from_num(3.1415926/2.0) );
+/// assert_eq!{ y.to_num::<f32>(), 9.536743e-7 };
+/// ``` 
+pub fn cos_precise_fixed<T>( x:T ) -> T
+    where T: FixedSigned
+{
+    return sin_precise_fixed( x+T::from_num(fixed::consts::FRAC_PI_2) );
+}
+
 /// Calculate atan(y/x) using a polynomial approximation.
 /// Utilizes the following polynomial to estimate the angle θ \[radians\].
 /// 
@@ -118,9 +213,7 @@ pub fn atan2_precise_float<T>( y:T, x:T ) -> T
 pub fn atan2_precise_fixed<T>( y: T, x: T ) -> T
     where T: Fixed
 {
-    let division: T = y/x;
-    return ( (division) + T::from_num(0.372003f32)*fixed_powi(division,3) ) 
-                        / (T::from_num(1) + T::from_num(0.703384f32)*fixed_powi(division,2) + T::from_num(0.043562f32)*fixed_powi(division,4) );
+    return atan_precise_fixed( y/x );
 }
 
 /// Calculate atan(x) using a polynomial approximation.
@@ -148,8 +241,117 @@ pub fn atan2_precise_fixed<T>( y: T, x: T ) -> T
 pub fn atan_precise_fixed<T>( x: T ) -> T
     where T: Fixed
 {
-    return ( (x) + T::from_num(0.372003f32)*fixed_powi(x,3) ) 
-            / (T::from_num(1) + T::from_num(0.703384f32)*fixed_powi(x,2) + T::from_num(0.043562f32)*fixed_powi(x,4) );
+    // Both polynomials are even, and are evaluated in x².
+    let square = x*x;
+    let numerator = Polynomial::new( [T::from_num(1), T::from_num(0.372003f32)] ).evaluate( square );
+    let denominator = Polynomial::new( [T::from_num(1), T::from_num(0.703384f32), T::from_num(0.043562f32)] ).evaluate( square );
+    return x*numerator/denominator;
+}
+
+/// Calculate the four-quadrant atan2(y, x) in the -π<θ=<π range.
+///
+/// The angle is folded into the |θ|<=π/4 range, where `atan_precise_fixed` is accurate, and then unfolded.
+/// The angle of the origin is zero.
+///
+/// # Arguments
+///
+/// * `y` - Is the argument along the y or imaginary axis.
+/// * `x` - Is the argument along the x or real axis.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::utility as util;
+/// let arg = util::atan2_fixed( FixedI32::<U20>::from_num(-2), FixedI32::<U20>::from_num(-1) );
+/// assert_eq!{ arg.to_num::<f32>(), -2.0344934 };
+/// ```
+pub fn atan2_fixed<T>( y: T, x: T ) -> T
+    where T: FixedSigned
+{
+    let pi = T::from_num(fixed::consts::PI);
+    if x == T::from_num(0) && y == T::from_num(0)
+    {
+        return T::from_num(0);
+    }
+    if y.abs() <= x.abs()
+    {
+        let angle = atan_precise_fixed( y/x );
+        if T::from_num(0) <= x
+        {
+            return angle;
+        }
+        else if T::from_num(0) <= y
+        {
+            return angle+pi;
+        }
+        return angle-pi;
+    }
+    let angle = atan_precise_fixed( x/y );
+    if T::from_num(0) < y
+    {
+        return T::from_num(fixed::consts::FRAC_PI_2)-angle;
+    }
+    return -T::from_num(fixed::consts::FRAC_PI_2)-angle;
+}
+
+/// Calculate log2(x) of a fixed point scalar.
+/// The integer part is found from the position of the most significant bit.
+/// The fractional part is found bit by bit, by repeatedly squaring the mantissa.
+/// 
+/// The type must be able to hold the value 4.
+/// Non-positive arguments return the minimum value of the type.
+/// 
+/// # Example
+/// 
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::utility as util;
+/// let y = util::log2_fixed( FixedI32::<U20>::from_num(10) );
+/// assert_eq!{ y.to_num::<f32>(), 3.321928 };
+/// ``` 
+pub fn log2_fixed<T>( x:T ) -> T
+    where T: FixedSigned
+{
+    if x <= T::from_num(0)
+    {
+        return T::MIN;
+    }
+    let exponent = x.int_log2();
+    // Normalize the mantissa to the 1=<m<2 range.
+    let mut mantissa = if 0 <= exponent { x >> (exponent as u32) } else { x << (-exponent as u32) };
+    let mut result = T::from_num(exponent);
+    let mut bit    = T::from_num(0.5f32);
+    for _i in 0..T::FRAC_NBITS {
+        mantissa *= mantissa;
+        if T::from_num(2) <= mantissa
+        {
+            mantissa >>= 1;
+            result += bit;
+        }
+        bit >>= 1;
+    }
+    return result;
+}
+
+/// Calculate log10(x) of a fixed point scalar through `log2_fixed`.
+/// 
+/// # Example
+/// 
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::utility as util;
+/// let y = util::log10_fixed( FixedI32::<U20>::from_num(0.01) );
+/// assert_eq!{ y.to_num::<f32>(), -1.9999857 };
+/// ``` 
+pub fn log10_fixed<T>( x:T ) -> T
+    where T: FixedSigned
+{
+    if x <= T::from_num(0)
+    {
+        return T::MIN;
+    }
+    return log2_fixed(x)*T::from_num(fixed::consts::LOG10_2);
 }
 
 /*
@@ -170,4 +372,11 @@ mod tests {
     fn fpowi() {
         assert_eq!{super::fpowi(3.0,2), 9.0};
     }
+    #[test]
+    fn log2_fixed() {
+        use fixed::{types::extra::U20, FixedI32};
+        assert_eq!{super::log2_fixed( FixedI32::<U20>::from_num(1) ), 0};
+        assert_eq!{super::log2_fixed( FixedI32::<U20>::from_num(0.25) ), -2};
+        assert_eq!{super::log2_fixed( FixedI32::<U20>::from_num(1024) ), 10};
+    }
 }
\ No newline at end of file
@@ -0,0 +1,101 @@
+use fixed::traits::FixedSigned;
+use crate::utility;
+
+/// Window functions for spectral analysis and FIR filter design.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Window {
+    /// All ones.
+    Rectangular,
+    /// `w(n) = 0.5-0.5cos(2πn/(L-1))`
+    Hann,
+    /// `w(n) = 0.54-0.46cos(2πn/(L-1))`
+    Hamming,
+    /// `w(n) = 0.42-0.5cos(2πn/(L-1))+0.08cos(4πn/(L-1))`
+    Blackman,
+}
+
+/// Calculate a single coefficient of a symmetric window.
+///
+/// ## Arguments
+/// * `window` - The window function.
+/// * `index`  - The index of the coefficient, in the range 0..length-1.
+/// * `length` - The length L of the window.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::window::{self, Window};
+///
+/// let w:FixedI32<U20> = window::coefficient( Window::Hann, 2, 5 );
+/// assert_eq!{ w, 1 };
+/// ```
+pub fn coefficient<T>( window:Window, index:usize, length:usize ) -> T
+    where T: FixedSigned
+{
+    if length < 2
+    {
+        return T::from_num(1);
+    }
+    let tau   = T::from_num(fixed::consts::TAU);
+    let phase = tau*(T::from_num(index)/T::from_num(length-1));
+    match window {
+        Window::Rectangular => T::from_num(1),
+        Window::Hann        => T::from_num(0.5f32)-utility::cos_precise_fixed(phase)/T::from_num(2),
+        Window::Hamming     => T::from_num(0.54f32)-T::from_num(0.46f32)*utility::cos_precise_fixed(phase),
+        Window::Blackman    => T::from_num(0.42f32)-utility::cos_precise_fixed(phase)/T::from_num(2)
+                                +T::from_num(0.08f32)*utility::cos_precise_fixed(phase*T::from_num(2)),
+    }
+}
+
+/// Fill a buffer with a symmetric window of the same length.
+///
+/// ## Arguments
+/// * `window` - The window function.
+/// * `buffer` - The buffer to fill.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::window::{self, Window};
+///
+/// let mut w = [FixedI32::<U20>::from_num(0); 5];
+/// window::fill( Window::Hann, &mut w );
+/// assert_eq!{ w, [0.0, 0.5, 1.0, 0.5, 0.0] };
+/// ```
+pub fn fill<T>( window:Window, buffer:&mut [T] )
+    where T: FixedSigned
+{
+    let length = buffer.len();
+    for (n, item) in buffer.iter_mut().enumerate() {
+        *item = coefficient( window, n, length );
+    }
+}
+
+/// Fill a buffer with a periodic (DFT-even) window, the first L items of a symmetric window of length L+1.
+///
+/// Periodic windows add up to a constant when overlapped at suitable hops, e.g. Hann at L/2 or L/4, which suits the STFT.
+///
+/// ## Arguments
+/// * `window` - The window function.
+/// * `buffer` - The buffer to fill.
+///
+/// # Example
+///
+/// ```
+/// use fixed::{types::extra::U20, FixedI32};
+/// use integer_array::window::{self, Window};
+///
+/// let mut w = [FixedI32::<U20>::from_num(0); 4];
+/// window::fill_periodic( Window::Hann, &mut w );
+/// assert_eq!{ w, [0.0, 0.5, 1.0, 0.5] };
+/// ```
+pub fn fill_periodic<T>( window:Window, buffer:&mut [T] )
+    where T: FixedSigned
+{
+    let length = buffer.len()+1;
+    for (n, item) in buffer.iter_mut().enumerate() {
+        *item = coefficient( window, n, length );
+    }
+}
@@ -1,4 +1,3 @@
-use integer_array;
 use fixed::{types::extra::U18, FixedI32};
 
 // Testing of real types
@@ -28,4 +27,36 @@ fn real() {
     let x = CArr11::new_from_i32( 5, 2 );
     assert_eq!{ x.real() , Arr11::new_from_i32(5) };
 
-}
\ No newline at end of file
+}
+// The squared deviations of a long array add up beyond the range of the type.
+#[test]
+fn statistics_use_wide_accumulators() {
+    use fixed::{types::extra::U20, FixedI32};
+    integer_array::declare_array_real!( Arr256, 256, FixedI32<U20> );
+    let mut x = Arr256::new_from_i32( 0 );
+    let mut state:u32 = 1;
+    for item in x.data.iter_mut() {
+        state = state.wrapping_mul(1103515245).wrapping_add(12345);
+        *item = FixedI32::<U20>::from_num( ((state >> 8) as f64)/((1u32 << 23) as f64)*10.0-8.0 );
+    }
+    let values: Vec<f64> = x.data.iter().map( |item| item.to_num::<f64>() ).collect();
+    let mean = values.iter().sum::<f64>()/256.0;
+    let moment = |order:i32| values.iter().map( |item| (item-mean).powi(order) ).sum::<f64>()/256.0;
+    let rms = (values.iter().map( |item| item*item ).sum::<f64>()/256.0).sqrt();
+
+    assert!{ (x.mean().to_num::<f64>()-mean).abs() < 1e-5 };
+    assert!{ (x.var().to_num::<f64>()-moment(2)).abs() < 1e-4 };
+    assert!{ (x.std_dev().to_num::<f64>()-moment(2).sqrt()).abs() < 1e-5 };
+    assert!{ (x.rms().to_num::<f64>()-rms).abs() < 1e-5 };
+    assert!{ (x.skewness().to_num::<f64>()-moment(3)/moment(2).powf(1.5)).abs() < 1e-4 };
+    assert!{ (x.kurtosis().to_num::<f64>()-(moment(4)/moment(2).powi(2)-3.0)).abs() < 1e-4 };
+
+    // The powers of full-range items of a type without fractional bits, which leave the variance saturated.
+    use fixed::types::extra::U0;
+    integer_array::declare_array_real!( Arr16, 16, FixedI32<U0> );
+    let y = Arr16::from( core::array::from_fn( |n| if n%2 == 0 { FixedI32::<U0>::MIN } else { FixedI32::<U0>::MAX } ) );
+    assert_eq!{ y.mean(), 0 };
+    assert_eq!{ y.var(), FixedI32::<U0>::MAX };
+    assert_eq!{ y.skewness(), 0 };
+    assert_eq!{ y.kurtosis(), -2 };
+}